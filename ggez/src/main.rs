@@ -1,5 +1,6 @@
 use ggez::*;
 use ggez::graphics::Color;
+use ggez::input::keyboard::{KeyCode, KeyInput, KeyMods};
 
 // pixel conversion information
 // the 'resolution' of the video signal was 455x262 clock signals (60Hz VSYNC)
@@ -20,6 +21,9 @@ const VBLANK: i32 = 16;
 const HSHIFT: i32 = 16;
 const PADDLE_MOVE_BY: f32 = 1.0;
 const WIN_SCORE: i32 = 11;
+// the original hardware advanced one frame per VSYNC, so physics steps at a fixed 60Hz
+const TICK_RATE: u32 = 60;
+const TICK_DELTA: f32 = 1.0 / TICK_RATE as f32;
 
 // utility funcs for converting pong timing values to pixels
 // the original circuitry resulted in the net being shifted to the left instead
@@ -42,49 +46,324 @@ fn vclk_to_px(vclk: i32) -> i32 {
     (vclk as f32 * PX_UNIT_HEIGHT) as i32
 }
 
+// the field is always laid out in VIEWPORT_WIDTH x VIEWPORT_HEIGHT units
+// when the window is a different size (fullscreen), scale uniformly and pad the
+// leftover space so the field letterboxes instead of stretching
+fn letterbox_coords(window_width: f32, window_height: f32) -> graphics::Rect {
+    let scale = (window_width / VIEWPORT_WIDTH).min(window_height / VIEWPORT_HEIGHT);
+    let width = window_width / scale;
+    let height = window_height / scale;
+    let xoffset = (width - VIEWPORT_WIDTH) / 2.0;
+    let yoffset = (height - VIEWPORT_HEIGHT) / 2.0;
+    graphics::Rect::new(-xoffset, -yoffset, width, height)
+}
+
 struct Net {}
 impl Net {
-    fn draw(&mut self, ctx: &mut Context) {
+    // the net is triggered at 256H from the HRST signal
+    // segments are 4V tall and repeat every 8V
+    fn draw(&mut self, ctx: &mut Context, canvas: &mut graphics::Canvas, color: Color) {
+        let net_left_edge = hclk_to_xpos(256) as f32;
         let net_width = hclk_to_px(1) as u32;
         let seg_height = vclk_to_px(4) as u32;
         let seg_spacing: usize = vclk_to_px(8).try_into().unwrap();
-        let image = graphics::Image::from_color(&ctx.gfx, net_width, seg_height, Some(Color::WHITE));
+        let image = graphics::Image::from_color(&ctx.gfx, net_width, seg_height, Some(color));
         let mut segments = graphics::InstanceArray::new(&ctx.gfx, image);
-        let mut canvas = graphics::Canvas::from_frame(&ctx.gfx, None);
         for i in (0..VIEWPORT_HEIGHT as i32).step_by(seg_spacing) {
-            let ypos = i as f32 - VIEWPORT_HEIGHT/2.0;
-            let loc = glam::vec2(0.0, ypos);
+            let loc = glam::vec2(net_left_edge, i as f32);
             segments.push(graphics::DrawParam::default().dest(loc));
-            println!("{:?}", segments);
         }
         canvas.draw(&segments, graphics::DrawParam::default());
     }
 }
 
+#[derive(Clone, Copy)]
+enum PlayerSide {
+    Left,
+    Right
+}
+
 struct Paddle {
+    ypos: f32,
+    side: PlayerSide,
+    up_held: bool,
+    down_held: bool,
+}
+
+impl Paddle {
+    fn from_side(side: PlayerSide) -> Self {
+        Self {
+            ypos: vclk_to_ypos(120) as f32,
+            side,
+            up_held: false,
+            down_held: false,
+        }
+    }
+
+    fn xpos(&self) -> f32 {
+        match self.side {
+            PlayerSide::Left => hclk_to_xpos(128) as f32,
+            PlayerSide::Right => hclk_to_xpos(128+256) as f32,
+        }
+    }
+
+    // W/S for the left paddle, Up/Down for the right, same as the Godot input map
+    fn set_key_held(&mut self, keycode: KeyCode, held: bool) {
+        match (self.side, keycode) {
+            (PlayerSide::Left, KeyCode::W) | (PlayerSide::Right, KeyCode::Up) => self.up_held = held,
+            (PlayerSide::Left, KeyCode::S) | (PlayerSide::Right, KeyCode::Down) => self.down_held = held,
+            _ => (),
+        }
+    }
 
+    fn update(&mut self, delta: f32) {
+        if self.up_held { self.move_up(delta) }
+        if self.down_held { self.move_down(delta) }
+    }
+
+    // the paddles could only reach the top line of the score counter, or 32V
+    fn move_up(&mut self, delta: f32) {
+        let min_ypos = vclk_to_ypos(32) as f32;
+        let new_ypos = self.ypos - PADDLE_MOVE_BY * VIEWPORT_HEIGHT * delta;
+        self.ypos = new_ypos.max(min_ypos);
+    }
+
+    // and the bottom was assumed to be around 16V from the bottom of the screen
+    fn move_down(&mut self, delta: f32) {
+        let bat_height = vclk_to_px(16);
+        let max_ypos = VIEWPORT_HEIGHT - (vclk_to_px(16) + bat_height) as f32;
+        let new_ypos = self.ypos + PADDLE_MOVE_BY * VIEWPORT_HEIGHT * delta;
+        self.ypos = new_ypos.min(max_ypos);
+    }
+
+    // the paddle is 4H wide and 16V tall
+    fn draw(&self, canvas: &mut graphics::Canvas, color: Color) {
+        let rect = graphics::Rect::new(self.xpos(), self.ypos, hclk_to_px(4) as f32, vclk_to_px(16) as f32);
+        canvas.draw(&graphics::Quad, graphics::DrawParam::default().dest_rect(rect).color(color));
+    }
+}
+
+//    _a_
+// f |_g_| b
+// e |___| c
+//     d
+// converts to an array of values [a, b, c, d, e, f, g]
+fn n_to_seven_segment(n: i32) -> Option<[i8; 7]> {
+    match n {
+        0 => Some([1, 1, 1, 1, 1, 1, 0]),
+        1 => Some([0, 1, 1, 0, 0, 0, 0]),
+        2 => Some([1, 1, 0, 1, 1, 0, 1]),
+        3 => Some([1, 1, 1, 1, 0, 0, 1]),
+        4 => Some([0, 1, 1, 0, 0, 1, 1]),
+        5 => Some([1, 0, 1, 1, 0, 1, 1]),
+        6 => Some([1, 0, 1, 1, 1, 1, 1]),
+        7 => Some([1, 1, 1, 0, 0, 0, 0]),
+        8 => Some([1, 1, 1, 1, 1, 1, 1]),
+        9 => Some([1, 1, 1, 0, 0, 1, 1]),
+        _ => None
+    }
+}
+
+// (hclk offset, vclk offset, width, height) of each segment a-g
+const SEGMENT_CLKS: [(i32, i32, i32, i32); 7] = [
+    (0, 0, 16, 4),
+    (12, 0, 4, 16),
+    (12, 16, 4, 16),
+    (0, 29, 16, 4),
+    (0, 16, 4, 16),
+    (0, 0, 4, 16),
+    (0, 13, 16, 4),
+];
+
+fn draw_seven_segment(canvas: &mut graphics::Canvas, n: i32, hclk: i32, vclk: i32, color: Color) {
+    let Some(segments) = n_to_seven_segment(n) else { return };
+    for (seg_is_on, (h, v, w, ht)) in std::iter::zip(segments, SEGMENT_CLKS) {
+        if seg_is_on == 1 {
+            let rect = graphics::Rect::new(
+                hclk_to_xpos(hclk+h) as f32,
+                vclk_to_ypos(vclk+v) as f32,
+                hclk_to_px(w) as f32,
+                vclk_to_px(ht) as f32,
+            );
+            canvas.draw(&graphics::Quad, graphics::DrawParam::default().dest_rect(rect).color(color));
+        }
+    }
+}
+
+// the score windows were positioned 32V from the top of the screen
+// ones digits sit at 175H and 367H, tens digits 32H to the left of them
+fn draw_score(canvas: &mut graphics::Canvas, score: &[i32; 2], color: Color) {
+    let offset_vclk = 32;
+    for (player, score) in score.iter().enumerate() {
+        let ones_hclk = 175 + (player as i32)*192;
+        if score / 10 != 0 {
+            draw_seven_segment(canvas, score / 10, ones_hclk - 32, offset_vclk, color);
+        }
+        draw_seven_segment(canvas, score % 10, ones_hclk, offset_vclk, color);
+    }
+}
+
+fn draw_centered_text(canvas: &mut graphics::Canvas, text: &str, ypos: f32, scale: f32, color: Color) {
+    let mut text = graphics::Text::new(text);
+    text.set_scale(scale).set_layout(graphics::TextLayout::center());
+    canvas.draw(&text, graphics::DrawParam::default().dest(glam::vec2(VIEWPORT_WIDTH/2.0, ypos)).color(color));
+}
+
+// the display color of the original depended on the monitor it was hooked up to
+#[derive(Clone, Copy)]
+enum ColorScheme {
+    White,
+    Amber,
+    Green,
+}
+
+impl ColorScheme {
+    fn color(&self) -> Color {
+        match self {
+            ColorScheme::White => Color::WHITE,
+            ColorScheme::Amber => Color::from_rgb(255, 176, 0),
+            ColorScheme::Green => Color::from_rgb(51, 255, 51),
+        }
+    }
+
+    fn next(&self) -> ColorScheme {
+        match self {
+            ColorScheme::White => ColorScheme::Amber,
+            ColorScheme::Amber => ColorScheme::Green,
+            ColorScheme::Green => ColorScheme::White,
+        }
+    }
+}
+
+enum Mode {
+    Menu,
+    Playing,
 }
 
 struct State {
+    mode: Mode,
     net: Net,
     paddles: [Paddle; 2],
+    score: [i32; 2],
+    fullscreen: bool,
+    paused: bool,
+    color_scheme: ColorScheme,
+    screen_coords: graphics::Rect,
 }
 
 impl State {
     fn new() -> Self {
         Self {
+            mode: Mode::Menu,
             net: Net {},
-            paddles: [Paddle {}, Paddle {}],
+            paddles: [Paddle::from_side(PlayerSide::Left), Paddle::from_side(PlayerSide::Right)],
+            score: [0, 0],
+            fullscreen: false,
+            paused: false,
+            color_scheme: ColorScheme::White,
+            screen_coords: letterbox_coords(VIEWPORT_WIDTH, VIEWPORT_HEIGHT),
+        }
+    }
+
+    fn toggle_fullscreen(&mut self, ctx: &mut Context) -> ggez::GameResult {
+        self.fullscreen = !self.fullscreen;
+        let fullscreen_type = if self.fullscreen {
+            conf::FullscreenType::Desktop
+        } else {
+            conf::FullscreenType::Windowed
+        };
+        ctx.gfx.set_fullscreen(fullscreen_type)?;
+        let (width, height) = ctx.gfx.drawable_size();
+        self.screen_coords = letterbox_coords(width, height);
+        Ok(())
+    }
+
+    fn set_color_scheme(&mut self, scheme: ColorScheme) {
+        self.color_scheme = scheme;
+    }
+
+    fn new_game(&mut self) {
+        self.paddles = [Paddle::from_side(PlayerSide::Left), Paddle::from_side(PlayerSide::Right)];
+        self.score = [0, 0];
+        self.paused = false;
+        self.mode = Mode::Playing;
+    }
+
+    // advances the simulation by exactly one tick of TICK_DELTA seconds
+    fn step(&mut self, delta: f32) {
+        if !matches!(self.mode, Mode::Playing) {
+            return
+        }
+        for paddle in self.paddles.iter_mut() {
+            paddle.update(delta);
         }
     }
 }
 
 impl event::EventHandler<error::GameError> for State {
+  // ggez keeps a residual time accumulator, so this runs as many fixed ticks as have
+  // elapsed since the last frame regardless of the display refresh rate
+  // ticks are still drained while paused so unpausing doesn't fast-forward
   fn update(&mut self, ctx: &mut Context) -> ggez::GameResult {
-    self.net.draw(ctx);
+    while ctx.time.check_update_time(TICK_RATE) {
+        if !self.paused {
+            self.step(TICK_DELTA);
+        }
+    }
     Ok(())
   }
+
   fn draw(&mut self, ctx: &mut Context) -> ggez::GameResult {
+    let color = self.color_scheme.color();
+    let mut canvas = graphics::Canvas::from_frame(&ctx.gfx, Color::BLACK);
+    canvas.set_screen_coordinates(self.screen_coords);
+    match self.mode {
+        Mode::Menu => {
+            draw_centered_text(&mut canvas, "PONG", VIEWPORT_HEIGHT/3.0, 64.0, color);
+            draw_centered_text(&mut canvas, "Press Enter to start", VIEWPORT_HEIGHT*2.0/3.0, 24.0, color);
+        }
+        Mode::Playing => {
+            self.net.draw(ctx, &mut canvas, color);
+            draw_score(&mut canvas, &self.score, color);
+            for paddle in self.paddles.iter() {
+                paddle.draw(&mut canvas, color);
+            }
+        }
+    }
+    canvas.finish(&mut ctx.gfx)
+  }
+
+  fn key_down_event(&mut self, ctx: &mut Context, input: KeyInput, repeated: bool) -> ggez::GameResult {
+    if repeated {
+        return Ok(())
+    }
+    if let Some(keycode) = input.keycode {
+        for paddle in self.paddles.iter_mut() {
+            paddle.set_key_held(keycode, true);
+        }
+    }
+    match input.keycode {
+        Some(KeyCode::F11) => self.toggle_fullscreen(ctx)?,
+        Some(KeyCode::Return) if input.mods.contains(KeyMods::ALT) => self.toggle_fullscreen(ctx)?,
+        Some(KeyCode::Return) if !matches!(self.mode, Mode::Playing) => self.new_game(),
+        Some(KeyCode::P) if matches!(self.mode, Mode::Playing) => self.paused = !self.paused,
+        Some(KeyCode::C) => self.set_color_scheme(self.color_scheme.next()),
+        _ => (),
+    }
+    Ok(())
+  }
+
+  fn key_up_event(&mut self, _ctx: &mut Context, input: KeyInput) -> ggez::GameResult {
+    if let Some(keycode) = input.keycode {
+        for paddle in self.paddles.iter_mut() {
+            paddle.set_key_held(keycode, false);
+        }
+    }
+    Ok(())
+  }
+
+  fn resize_event(&mut self, _ctx: &mut Context, width: f32, height: f32) -> ggez::GameResult {
+    self.screen_coords = letterbox_coords(width, height);
     Ok(())
   }
 }
@@ -5,8 +5,19 @@
 
 use std::convert::TryInto;
 use std::iter;
+use std::ops::{Deref, DerefMut};
+use std::hint::black_box;
+use std::time::Instant;
+use std::sync::atomic::{AtomicU32, Ordering};
 use godot::prelude::*;
-use godot::engine::{Polygon2D, CollisionPolygon2D, CollisionShape2D, RectangleShape2D, IPolygon2D, Area2D, IArea2D};
+use godot::engine::utilities::{randf_range, randi_range};
+use godot::engine::global::{MouseButton, HorizontalAlignment, Error};
+use godot::engine::notify::NodeNotification;
+use godot::engine::node::ProcessMode;
+use godot::engine::canvas_item::TextureFilter;
+use godot::engine::file_access::ModeFlags;
+use godot::engine::display_server::VSyncMode;
+use godot::engine::{Polygon2D, CollisionPolygon2D, CollisionShape2D, RectangleShape2D, IPolygon2D, Area2D, IArea2D, Engine, DisplayServer, IObject, Label, ILabel, Line2D, Sprite2D, Texture2D, ColorRect, Camera2D, ConfigFile, SubViewport, SubViewportContainer, InputMap, Control, IControl, Button, VBoxContainer, Os, FileAccess, Time, InputEvent, InputEventScreenTouch, InputEventScreenDrag, InputEventMouseMotion};
 
 // pixel conversion information
 // the 'resolution' of the video signal was 455x262 clock signals (60Hz VSYNC)
@@ -23,757 +34,4646 @@ const VIEWPORT_WIDTH: i32 = 640;
 const VIEWPORT_HEIGHT: i32 = 480;
 const PX_UNIT_WIDTH: f32 = 1.68;
 const PX_UNIT_HEIGHT: f32 = 1.95;
+// defaults, the conversions use GameConfig::hblank and vblank so they can be tweaked at runtime
 const HBLANK: i32 = 81;
 const VBLANK: i32 = 16;
 const HSHIFT: i32 = 16;
 const PADDLE_MOVE_BY: f32 = 1.0;
+// roughly the middle of the field
+const PADDLE_START_VCLK: i32 = 120;
 const WIN_SCORE: i32 = 11;
+// the score display only has two digits
+const SCORE_DISPLAY_MAX: i32 = 99;
+const SCORE_FLASH_DURATION: f64 = 0.1;
+// seconds a score spends on, then off, while blinking at game point
+const GAME_POINT_BLINK_PERIOD: f64 = 0.4;
+const PADDLE_FLASH_DURATION: f64 = 0.08;
+// simulation ticks per second, the original ran once per 60Hz VSYNC
+const TICK_RATE: i32 = 60;
+// leaves attract mode, rebindable with GameConfig::set_start_event
+const START_ACTION: &str = "enter";
+// seconds after a match is won that START still plays another match
+const REMATCH_WINDOW: f64 = 10.0;
+// replay files start with "PONG" and then the format version, see Main::export_replay
+const REPLAY_MAGIC: u32 = u32::from_le_bytes(*b"PONG");
+const REPLAY_VERSION: u32 = 1;
+// the settings kept between runs, see GameConfig::save_settings
+const SETTINGS_PATH: &str = "user://settings.cfg";
+// fraction of the distance to the ball the camera covers per second when following it
+const CAMERA_FOLLOW_RATE: f32 = 5.0;
 
 struct Pong;
 
 #[gdextension]
-unsafe impl ExtensionLibrary for Pong {}
+unsafe impl ExtensionLibrary for Pong {
+    fn on_level_init(level: InitLevel) {
+        if level == InitLevel::Scene {
+            let mut engine = Engine::singleton();
+            engine.register_singleton("GameConfig".into(), GameConfig::new_alloc().upcast());
+            engine.register_singleton("GameEvents".into(), GameEvents::new_alloc().upcast());
+        }
+    }
+
+    fn on_level_deinit(level: InitLevel) {
+        if level == InitLevel::Scene {
+            let mut engine = Engine::singleton();
+            for name in ["GameConfig", "GameEvents"] {
+                if let Some(singleton) = engine.get_singleton(name.into()) {
+                    engine.unregister_singleton(name.into());
+                    singleton.free();
+                }
+            }
+        }
+    }
+}
+
+// the display color of the original depended on the monitor it was hooked up to
+#[derive(Clone, Copy)]
+enum ColorScheme {
+    White,
+    Amber,
+    Green,
+}
+
+impl ColorScheme {
+    fn from_name(name: &str) -> Option<ColorScheme> {
+        match name {
+            "white" => Some(ColorScheme::White),
+            "amber" => Some(ColorScheme::Amber),
+            "green" => Some(ColorScheme::Green),
+            _ => None
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            ColorScheme::White => Color::WHITE,
+            ColorScheme::Amber => Color::from_rgb(1.0, 0.69, 0.0),
+            ColorScheme::Green => Color::from_rgb(0.2, 1.0, 0.2),
+        }
+    }
+}
+
+// scales the ball and paddle speeds together
+// at fast, the quickest ball moves ~8.5px a frame at 60fps, which is still less than
+// the combined width of the ball and paddle collision shapes, so hits aren't skipped
+#[derive(Clone, Copy)]
+enum GameSpeed {
+    Slow,
+    Normal,
+    Fast,
+}
+
+impl GameSpeed {
+    fn from_name(name: &str) -> Option<GameSpeed> {
+        match name {
+            "slow" => Some(GameSpeed::Slow),
+            "normal" => Some(GameSpeed::Normal),
+            "fast" => Some(GameSpeed::Fast),
+            _ => None
+        }
+    }
+
+    fn multiplier(&self) -> f32 {
+        match self {
+            GameSpeed::Slow => 0.5,
+            GameSpeed::Normal => 1.0,
+            GameSpeed::Fast => 1.5,
+        }
+    }
+}
+
+// bundles the cosmetic settings into a single switch
+#[derive(Clone, Copy)]
+enum RenderStyle {
+    // white rects and an off-center net like the original, no extra effects
+    Classic,
+    Enhanced,
+}
+
+impl RenderStyle {
+    fn from_name(name: &str) -> Option<RenderStyle> {
+        match name {
+            "classic" => Some(RenderStyle::Classic),
+            "enhanced" => Some(RenderStyle::Enhanced),
+            _ => None
+        }
+    }
+}
+
+// game settings shared by every node, registered as an engine singleton so
+// they can also be changed from the editor/gdscript
+#[derive(GodotClass)]
+#[class(base=Object)]
+struct GameConfig {
+    settings: Settings,
+    base: Base<Object>
+}
+
+// the settings read through GameConfig, kept apart from its base so the defaults can be made without the engine
+struct Settings {
+    color: Color,
+    scoring_rule: ScoringRule,
+    // reproduce quirks of the original hardware rather than 'fixing' them
+    accurate: bool,
+    // draw the net where the hardware actually put it instead of the center
+    offset_net: bool,
+    show_net: bool,
+    show_collision_segments: bool,
+    score_flash: bool,
+    // dim the paddle for a moment on every return
+    paddle_hit_flash: bool,
+    digit_style: DigitStyle,
+    // draw the scores as ordinary numerals in the label font instead of seven segment digits
+    numeral_scores: bool,
+    // blink the score of a player who is one point from winning, see ScoreDisplay::is_blinking_off
+    game_point_blink: bool,
+    // None draws both paddles in the main color like the original
+    paddle_colors: Option<[Color; 2]>,
+    // None is a two player game, otherwise the right paddle is computer controlled
+    ai_difficulty: Option<Difficulty>,
+    // number of games in a match, the first player to win a majority takes the match
+    match_length: i32,
+    serve_delay: f64,
+    // paddles follow touches/drags on their half of the screen
+    touch_controls: bool,
+    // number of collision segments on each paddle, always odd so there's a flat middle segment
+    paddle_segments: i32,
+    // debug mode where the simulation only advances one frame at a time on request
+    frame_step: bool,
+    step_requested: bool,
+    // swaps up and down for each player, left then right
+    invert_controls: [bool; 2],
+    game_speed: GameSpeed,
+    ball_spin: bool,
+    // tint the ball by its speed tier, see Ball::tier_color
+    speed_tier_colors: bool,
+    // the ball speeds up to the next tier after this many returns in a rally, 4 then 12 originally
+    speed_tier_hits: [i32; 2],
+    // xvel of each tier, slowest first, see xvel_to_width_sec for what these work out to
+    speed_tier_xvels: [i32; 3],
+    // shows a PracticeAim marker where the ball will cross the paddles
+    practice_mode: bool,
+    // shows a ControlsHint at the start of each game and in attract mode
+    controls_hint: bool,
+    // balls that get past a paddle are just served again, nobody scores and the game never ends
+    endless_rally: bool,
+    // pixels, the ceiling and floor sit just outside the field so this only matters for fast balls
+    bounds_thickness: i32,
+    // pixels, the scoring walls are this thick and sit wall_offset outside the left and right edges
+    wall_thickness: i32,
+    wall_offset: i32,
+    paddle_start_vclk: i32,
+    // see Ball::serve_lock
+    serve_lock: bool,
+    // the server can hold turbo_l/turbo_r to launch the ball one speed tier faster, see Ball::turbo
+    turbo_serve: bool,
+    // replaces the wall on this side with a TrainingWall for solo practice
+    training_wall: Option<PlayerSide>,
+    // the yvel returned by the edge segments of the paddle
+    max_yvel: i32,
+    // segments either side of the middle one that also return the ball flat, 0 is the original
+    flat_deadzone: i32,
+    // replaces the computed segment yvels when set, one per segment from the top of the paddle
+    segment_yvels: Option<Vec<i32>>,
+    // on by default, turn off to keep the game running in the background e.g. while streaming
+    pause_on_focus_loss: bool,
+    // stop the ball where it crossed the wall so the exit is visible until the next serve,
+    // the original just let it carry on off screen
+    freeze_on_score: bool,
+    // seconds without input before an unstarted game drops to attract mode, None never does
+    attract_timeout: Option<f64>,
+    // the walls knock the ball's yvel up or down a step in attract mode so the demo rally varies
+    attract_english: bool,
+    // swaps which paddle each set of keys moves, the sides themselves and the scoring stay put
+    mirror_controls: bool,
+    // in clocks each way, 4 is the original
+    ball_size: i32,
+    // (hclk, vclk) the ball is served from, see ball_spawn
+    ball_spawn_clk: (i32, i32),
+    // drawn in place of the square, see Ball::set_texture
+    ball_texture: Option<Gd<Texture2D>>,
+    // more than one is multiball, each ball scores on its own and they are served again once all are out
+    ball_count: i32,
+    // multiball only, balls bounce off each other instead of passing through
+    ball_collisions: bool,
+    // tells the paddles and ball apart by shape as well as color, see contrast_outline
+    high_contrast: bool,
+    // blanking intervals in clocks, see hclk_to_xpos and vclk_to_ypos
+    hblank: i32,
+    vblank: i32,
+    // distance of each paddle from the net, see paddle_hclk
+    paddle_offset_hclk: i32,
+    serve_rule: ServeRule,
+    collision_reset: CollisionReset,
+    // None leaves the individual cosmetic settings as they were set
+    render_style: Option<RenderStyle>,
+    // size the field is rendered at before being scaled up to the window, None draws straight to the window
+    render_resolution: Option<Vector2i>,
+    // how often the ball and paddles are moved, independent of the display's refresh rate
+    tick_rate: i32,
+    // rendering only, the simulation stays at tick_rate either way, 0 max_fps is uncapped
+    vsync: bool,
+    max_fps: i32,
+}
+
+impl Deref for GameConfig {
+    type Target = Settings;
+
+    fn deref(&self) -> &Settings {
+        &self.settings
+    }
+}
+
+impl DerefMut for GameConfig {
+    fn deref_mut(&mut self) -> &mut Settings {
+        &mut self.settings
+    }
+}
+
+#[godot_api]
+impl IObject for GameConfig {
+    fn init(base: Base<Object>) -> Self {
+        let touch_controls = Os::singleton().has_feature("mobile".into());
+        Self { settings: Settings::defaults(touch_controls), base }
+    }
+}
+
+impl Settings {
+    // the original hardware's values where there is one, touch_controls is whether this is a mobile build
+    fn defaults(touch_controls: bool) -> Self {
+        Self {
+            color: ColorScheme::White.color(),
+            scoring_rule: ScoringRule::FirstTo,
+            accurate: false,
+            offset_net: false,
+            show_net: true,
+            show_collision_segments: false,
+            score_flash: true,
+            paddle_hit_flash: false,
+            digit_style: DigitStyle::Chunky,
+            numeral_scores: false,
+            game_point_blink: false,
+            paddle_colors: None,
+            ai_difficulty: None,
+            match_length: 1,
+            serve_delay: 1.5,
+            touch_controls,
+            paddle_segments: DEFAULT_PADDLE_SEGMENTS,
+            frame_step: false,
+            step_requested: false,
+            invert_controls: [false, false],
+            game_speed: GameSpeed::Normal,
+            ball_spin: false,
+            speed_tier_colors: false,
+            speed_tier_hits: [4, 12],
+            speed_tier_xvels: [1, 2, 3],
+            practice_mode: false,
+            controls_hint: true,
+            endless_rally: false,
+            bounds_thickness: 10,
+            wall_thickness: 10,
+            wall_offset: 1,
+            paddle_start_vclk: PADDLE_START_VCLK,
+            serve_lock: false,
+            turbo_serve: false,
+            training_wall: None,
+            max_yvel: AUTHENTIC_MAX_YVEL,
+            flat_deadzone: 0,
+            segment_yvels: None,
+            pause_on_focus_loss: true,
+            freeze_on_score: false,
+            attract_timeout: Some(20.0),
+            attract_english: true,
+            mirror_controls: false,
+            ball_size: 4,
+            ball_spawn_clk: (256, 128),
+            ball_texture: None,
+            ball_count: 1,
+            ball_collisions: false,
+            high_contrast: false,
+            hblank: HBLANK,
+            vblank: VBLANK,
+            paddle_offset_hclk: 128,
+            serve_rule: ServeRule::ToLoser,
+            collision_reset: CollisionReset::AwayFromPaddle,
+            render_style: None,
+            render_resolution: None,
+            tick_rate: TICK_RATE,
+            vsync: true,
+            max_fps: 0,
+        }
+    }
+}
+
+#[godot_api]
+impl GameConfig {
+    #[signal]
+    fn config_changed();
+
+    // puts every setting back to the values from init, see Settings::defaults
+    // bindings changed with set_start_event live in the InputMap and are left as they are
+    #[func]
+    fn reset_defaults(&mut self) {
+        // config_changed listeners are connected to the base, which is kept
+        self.settings = Settings::defaults(Os::singleton().has_feature("mobile".into()));
+        let mode = if self.vsync { VSyncMode::ENABLED } else { VSyncMode::DISABLED };
+        DisplayServer::singleton().window_set_vsync_mode(mode);
+        Engine::singleton().set_physics_ticks_per_second(self.tick_rate);
+        Engine::singleton().set_max_fps(self.max_fps);
+        self.notify_changed();
+    }
+
+    // every setter ends with this so nodes that copied a setting when they were made can refresh it
+    // a setter that rejects its value returns before getting here
+    fn notify_changed(&mut self) {
+        self.base_mut().emit_signal("config_changed".into(), &[]);
+    }
+
+    #[func]
+    fn set_color(&mut self, color: Color) {
+        self.color = color;
+        self.notify_changed();
+    }
+
+    // one of "white", "amber" or "green"
+    #[func]
+    fn set_color_scheme(&mut self, name: GString) {
+        match ColorScheme::from_name(&name.to_string()) {
+            Some(scheme) => self.color = scheme.color(),
+            None => godot_warn!("unknown color scheme {name}"),
+        }
+        self.notify_changed();
+    }
+
+    #[func]
+    fn set_win_by_two(&mut self, enabled: bool) {
+        self.scoring_rule = if enabled { ScoringRule::WinByTwo } else { ScoringRule::FirstTo };
+        self.notify_changed();
+    }
+
+    // only applies on top of win by two, so enabling it also enables win by two
+    #[func]
+    fn set_sudden_death(&mut self, enabled: bool) {
+        self.scoring_rule = if enabled { ScoringRule::SuddenDeath } else { ScoringRule::WinByTwo };
+        self.notify_changed();
+    }
+
+    #[func]
+    fn set_accurate(&mut self, enabled: bool) {
+        if enabled && self.paddle_segments != DEFAULT_PADDLE_SEGMENTS {
+            godot_warn!("accurate mode uses one segment per line, paddle_segments {} is ignored", self.paddle_segments);
+        }
+        self.accurate = enabled;
+        self.offset_net = enabled;
+        self.notify_changed();
+    }
+
+    // takes effect at the start of the next game, nothing else relies on the net being there
+    #[func]
+    fn set_show_net(&mut self, enabled: bool) {
+        self.show_net = enabled;
+        self.notify_changed();
+    }
+
+    #[func]
+    fn set_score_flash(&mut self, enabled: bool) {
+        self.score_flash = enabled;
+        self.notify_changed();
+    }
+
+    #[func]
+    fn set_paddle_hit_flash(&mut self, enabled: bool) {
+        self.paddle_hit_flash = enabled;
+        self.notify_changed();
+    }
+
+    #[func]
+    fn set_numeral_scores(&mut self, enabled: bool) {
+        self.numeral_scores = enabled;
+        self.notify_changed();
+    }
+
+    // one of "chunky" or "thin"
+    #[func]
+    fn set_digit_style(&mut self, name: GString) {
+        match DigitStyle::from_name(&name.to_string()) {
+            Some(style) => self.digit_style = style,
+            None => godot_warn!("unknown digit style {name}"),
+        }
+        self.notify_changed();
+    }
+
+    #[func]
+    fn set_game_point_blink(&mut self, enabled: bool) {
+        self.game_point_blink = enabled;
+        self.notify_changed();
+    }
+
+    #[func]
+    fn set_paddle_colors(&mut self, left: Color, right: Color) {
+        self.paddle_colors = Some([left, right]);
+        self.notify_changed();
+    }
+
+    #[func]
+    fn set_side_paddle_colors(&mut self) {
+        self.paddle_colors = Some([Color::from_rgb(0.3, 0.5, 1.0), Color::from_rgb(1.0, 0.3, 0.3)]);
+        self.notify_changed();
+    }
+
+    #[func]
+    fn set_authentic_paddle_colors(&mut self) {
+        self.paddle_colors = None;
+        self.notify_changed();
+    }
+
+    // 0 is easy, 1 is medium, 2 is hard
+    #[func]
+    fn set_ai_opponent(&mut self, level: i32) {
+        self.ai_difficulty = Some(Difficulty::from_level(level));
+        self.notify_changed();
+    }
+
+    #[func]
+    fn set_two_player(&mut self) {
+        self.ai_difficulty = None;
+        self.notify_changed();
+    }
+
+    #[func]
+    fn set_match_length(&mut self, games: i32) {
+        self.match_length = games.max(1);
+        self.notify_changed();
+    }
+
+    #[func]
+    fn set_touch_controls(&mut self, enabled: bool) {
+        self.touch_controls = enabled;
+        self.notify_changed();
+    }
+
+    // takes effect on the next game, even counts are rounded up
+    #[func]
+    fn set_paddle_segments(&mut self, count: i32) {
+        if self.accurate {
+            godot_warn!("accurate mode uses one segment per line, paddle_segments {count} is ignored until it's turned off");
+        }
+        self.paddle_segments = count.max(1) | 1;
+        self.notify_changed();
+    }
+
+    // saved straight away, see save_settings
+    #[func]
+    fn set_invert_controls(&mut self, left: bool, right: bool) {
+        self.invert_controls = [left, right];
+        self.save_settings();
+        self.notify_changed();
+    }
+
+    // writes the settings that are kept between runs to SETTINGS_PATH, so far the inverted controls
+    #[func]
+    fn save_settings(&self) -> bool {
+        let mut file = ConfigFile::new_gd();
+        let [left, right] = self.invert_controls;
+        file.set_value("controls".into(), "invert_left".into(), left.to_variant());
+        file.set_value("controls".into(), "invert_right".into(), right.to_variant());
+        let error = file.save(SETTINGS_PATH.into());
+        if error != Error::OK {
+            godot_warn!("couldn't save the settings to {SETTINGS_PATH}: {error:?}");
+            return false
+        }
+        true
+    }
+
+    // a missing file or key keeps the current value, as does one of the wrong type
+    // Main loads these before the first game, so there is nothing to notify yet
+    #[func]
+    fn load_settings(&mut self) {
+        let mut file = ConfigFile::new_gd();
+        if file.load(SETTINGS_PATH.into()) != Error::OK {
+            return
+        }
+        let saved_bool = |key: &str, current: bool| {
+            if !file.has_section_key("controls".into(), key.into()) {
+                return current
+            }
+            file.get_value("controls".into(), key.into()).try_to::<bool>().unwrap_or(current)
+        };
+        let [left, right] = self.invert_controls;
+        self.invert_controls = [saved_bool("invert_left", left), saved_bool("invert_right", right)];
+    }
+
+    // one of "slow", "normal" or "fast"
+    #[func]
+    fn set_game_speed(&mut self, name: GString) {
+        match GameSpeed::from_name(&name.to_string()) {
+            Some(speed) => self.game_speed = speed,
+            None => godot_warn!("unknown game speed {name}"),
+        }
+        self.notify_changed();
+    }
+
+    // seconds between a point being scored and the next serve
+    #[func]
+    fn set_serve_delay(&mut self, seconds: f32) {
+        self.serve_delay = seconds.max(0.0) as f64;
+        self.notify_changed();
+    }
+
+    // ticks per second, anything other than 60 no longer matches the original's VSYNC
+    #[func]
+    fn set_tick_rate(&mut self, ticks: i32) {
+        self.tick_rate = ticks.clamp(10, 240);
+        Engine::singleton().set_physics_ticks_per_second(self.tick_rate);
+        self.notify_changed();
+    }
+
+    // applied straight away, e.g. vsync off with max_fps 60 for the original's pace on a fast monitor
+    #[func]
+    fn set_vsync(&mut self, enabled: bool) {
+        self.vsync = enabled;
+        let mode = if enabled { VSyncMode::ENABLED } else { VSyncMode::DISABLED };
+        DisplayServer::singleton().window_set_vsync_mode(mode);
+        self.notify_changed();
+    }
+
+    #[func]
+    fn set_max_fps(&mut self, fps: i32) {
+        self.max_fps = fps.max(0);
+        Engine::singleton().set_max_fps(self.max_fps);
+        self.notify_changed();
+    }
+
+    // cosmetic only, the original ball was a static square
+    #[func]
+    fn set_ball_spin(&mut self, enabled: bool) {
+        self.ball_spin = enabled;
+        self.notify_changed();
+    }
+
+    #[func]
+    fn set_speed_tier_colors(&mut self, enabled: bool) {
+        self.speed_tier_colors = enabled;
+        self.notify_changed();
+    }
+
+    // the second threshold is kept at or above the first
+    #[func]
+    fn set_speed_tier_hits(&mut self, medium: i32, fast: i32) {
+        let medium = medium.max(0);
+        self.speed_tier_hits = [medium, fast.max(medium)];
+        self.notify_changed();
+    }
+
+    // each is 1 to 3, the speeds the original could produce, e.g. 2, 2, 3 for a quicker opening
+    #[func]
+    fn set_speed_tier_xvels(&mut self, slow: i32, medium: i32, fast: i32) {
+        self.speed_tier_xvels = [slow, medium, fast].map(|xvel| xvel.clamp(1, 3));
+        self.notify_changed();
+    }
+
+    // takes effect at the start of the next game
+    #[func]
+    fn set_practice_mode(&mut self, enabled: bool) {
+        self.practice_mode = enabled;
+        self.notify_changed();
+    }
+
+    // takes effect at the start of the next game
+    #[func]
+    fn set_bounds_thickness(&mut self, px: i32) {
+        self.bounds_thickness = px.max(1);
+        self.notify_changed();
+    }
+
+    #[func]
+    fn set_wall_thickness(&mut self, px: i32) {
+        self.wall_thickness = px.max(1);
+        self.notify_changed();
+    }
+
+    // a negative offset would pull the walls over the field, where a ball could score before passing a paddle
+    #[func]
+    fn set_wall_offset(&mut self, px: i32) {
+        self.wall_offset = px.max(0);
+        self.notify_changed();
+    }
+
+    // in V lines from the top, e.g. 32 starts both paddles at the top of their range
+    // takes effect at the start of the next game
+    #[func]
+    fn set_paddle_start(&mut self, vclk: i32) {
+        self.paddle_start_vclk = vclk;
+        self.notify_changed();
+    }
+
+    // takes effect at the start of the next game
+    #[func]
+    fn set_serve_lock(&mut self, enabled: bool) {
+        self.serve_lock = enabled;
+        self.notify_changed();
+    }
+
+    // checked as each serve launches
+    #[func]
+    fn set_turbo_serve(&mut self, enabled: bool) {
+        self.turbo_serve = enabled;
+        self.notify_changed();
+    }
+
+    // "left" or "right", anything else goes back to scoring on both sides
+    // takes effect at the start of the next game
+    #[func]
+    fn set_training_wall(&mut self, side: GString) {
+        self.training_wall = PlayerSide::from_name(&side.to_string());
+        self.notify_changed();
+    }
+
+    // 3 is the original, up to 5 for steeper returns off the edges of the paddle
+    #[func]
+    fn set_max_yvel(&mut self, max_yvel: i32) {
+        self.max_yvel = max_yvel.clamp(1, MAX_YVEL_LIMIT);
+        self.notify_changed();
+    }
+
+    // a more forgiving paddle, e.g. 1 makes the 7 segment paddle's middle three all flat
+    // the outer segments keep their angles, accurate mode ignores this
+    #[func]
+    fn set_flat_deadzone(&mut self, segments: i32) {
+        self.flat_deadzone = segments.max(0);
+        self.notify_changed();
+    }
+
+    // one yvel per segment from the top of the paddle, e.g. [-3, -2, -1, 0, 1, 2, 3] for the default
+    // it has to cover every segment, 15 in accurate mode, an empty array goes back to the computed values
+    // values are clamped to +-max_yvel, takes effect at the start of the next game
+    #[func]
+    fn set_segment_yvels(&mut self, yvels: PackedInt32Array) {
+        self.segment_yvels = if yvels.is_empty() { None } else { Some(yvels.to_vec()) };
+        self.notify_changed();
+    }
+
+    // what each paddle segment returns the ball at, built once per paddle rather than on every hit
+    // a custom table of the wrong length is ignored, since the segments it was written for aren't there
+    fn segment_yvel_table(&self) -> Vec<i32> {
+        let count = if self.accurate { PADDLE_LINE_YVEL.len() as i32 } else { self.paddle_segments };
+        match &self.segment_yvels {
+            Some(yvels) if yvels.len() == count as usize => {
+                yvels.iter().map(|&yvel| yvel.clamp(-self.max_yvel, self.max_yvel)).collect()
+            }
+            custom => {
+                if let Some(yvels) = custom {
+                    godot_warn!("{} segment yvels set for {count} segments, using the defaults", yvels.len());
+                }
+                (0..count)
+                    .map(|index| segment_to_yvel(index, count, self.accurate, self.max_yvel, self.flat_deadzone))
+                    .collect()
+            }
+        }
+    }
+
+    #[func]
+    fn set_pause_on_focus_loss(&mut self, enabled: bool) {
+        self.pause_on_focus_loss = enabled;
+        self.notify_changed();
+    }
+
+    #[func]
+    fn set_freeze_on_score(&mut self, enabled: bool) {
+        self.freeze_on_score = enabled;
+        self.notify_changed();
+    }
+
+    // 0 or less turns it off
+    #[func]
+    fn set_attract_timeout(&mut self, seconds: f32) {
+        self.attract_timeout = if seconds > 0.0 { Some(seconds as f64) } else { None };
+        self.notify_changed();
+    }
+
+    // puts player 1, using the up_l/dn_l keys, on the right
+    #[func]
+    fn set_mirror_controls(&mut self, enabled: bool) {
+        self.mirror_controls = enabled;
+        self.notify_changed();
+    }
+
+    // bigger is easier to hit, takes effect at the start of the next game
+    #[func]
+    fn set_ball_size(&mut self, clks: i32) {
+        self.ball_size = clks.clamp(1, 16);
+        self.notify_changed();
+    }
+
+    // null goes back to the square, takes effect at the start of the next game
+    #[func]
+    fn set_ball_texture(&mut self, texture: Option<Gd<Texture2D>>) {
+        self.ball_texture = texture;
+        self.notify_changed();
+    }
+
+    // 256H 128V is the middle of the field, takes effect at the start of the next game
+    #[func]
+    fn set_ball_spawn(&mut self, hclk: i32, vclk: i32) {
+        self.ball_spawn_clk = (hclk, vclk);
+        self.notify_changed();
+    }
+
+    // takes effect at the start of the next game
+    #[func]
+    fn set_ball_count(&mut self, count: i32) {
+        self.ball_count = count.clamp(1, 8);
+        self.notify_changed();
+    }
+
+    #[func]
+    fn set_controls_hint(&mut self, enabled: bool) {
+        self.controls_hint = enabled;
+        self.notify_changed();
+    }
+
+    #[func]
+    fn set_endless_rally(&mut self, enabled: bool) {
+        self.endless_rally = enabled;
+        self.notify_changed();
+    }
+
+    #[func]
+    fn set_attract_english(&mut self, enabled: bool) {
+        self.attract_english = enabled;
+        self.notify_changed();
+    }
+
+    // takes effect at the start of the next game
+    #[func]
+    fn set_ball_collisions(&mut self, enabled: bool) {
+        self.ball_collisions = enabled;
+        self.notify_changed();
+    }
+
+    // takes effect at the start of the next game
+    #[func]
+    fn set_high_contrast(&mut self, enabled: bool) {
+        self.high_contrast = enabled;
+        self.notify_changed();
+    }
+
+    // replaces whatever starts a game from attract mode, e.g. with an InputEventKey
+    #[func]
+    fn set_start_event(&mut self, event: Gd<InputEvent>) {
+        let mut input_map = InputMap::singleton();
+        input_map.action_erase_events(START_ACTION.into());
+        input_map.action_add_event(START_ACTION.into(), event);
+        self.notify_changed();
+    }
+
+    // for experimenting with the timing, positions already on screen only move once they are redrawn
+    #[func]
+    fn set_hblank(&mut self, hclk: i32) {
+        if hclk <= 0 {
+            godot_warn!("hblank must be positive, got {hclk}");
+            return
+        }
+        self.hblank = hclk;
+        self.notify_changed();
+    }
+
+    // (hblank, vblank), read once by callers and passed to the clock to position conversions
+    fn blanking(&self) -> (i32, i32) {
+        (self.hblank, self.vblank)
+    }
+
+    #[func]
+    fn set_vblank(&mut self, vclk: i32) {
+        if vclk <= 0 {
+            godot_warn!("vblank must be positive, got {vclk}");
+            return
+        }
+        self.vblank = vclk;
+        self.notify_changed();
+    }
+
+    // smaller brings the paddles in towards the net, everything reads it through paddle_hclk so it applies from the next frame
+    // kept far enough out that the ball can still clear them before the net, see Ball::process
+    #[func]
+    fn set_paddle_offset(&mut self, hclk: i32) {
+        self.paddle_offset_hclk = hclk.clamp(32, 170);
+        self.notify_changed();
+    }
+
+    // one of "to_loser", "to_winner" or "alternate"
+    #[func]
+    fn set_serve_rule(&mut self, name: GString) {
+        match ServeRule::from_name(&name.to_string()) {
+            Some(rule) => self.serve_rule = rule,
+            None => godot_warn!("unknown serve rule {name}"),
+        }
+        self.notify_changed();
+    }
+
+    // one of "center", "away" or "cooldown"
+    #[func]
+    fn set_collision_reset(&mut self, name: GString) {
+        match CollisionReset::from_name(&name.to_string()) {
+            Some(reset) => self.collision_reset = reset,
+            None => godot_warn!("unknown collision reset {name}"),
+        }
+        self.notify_changed();
+    }
+
+    // one of "classic" or "enhanced", applied at the start of the next game
+    #[func]
+    fn set_render_style(&mut self, name: GString) {
+        match RenderStyle::from_name(&name.to_string()) {
+            Some(style) => self.render_style = Some(style),
+            None => godot_warn!("unknown render style {name}"),
+        }
+        self.notify_changed();
+    }
+
+    // e.g. 374x246 for one pixel per clock of active video, 0 for either turns it off
+    // applied at the start of the next game
+    #[func]
+    fn set_render_resolution(&mut self, width: i32, height: i32) {
+        self.render_resolution = if width > 0 && height > 0 {
+            Some(Vector2i::new(width, height))
+        } else {
+            None
+        };
+        self.notify_changed();
+    }
+
+    // overrides the individual cosmetic settings, gameplay settings are left alone
+    fn apply_render_style(&mut self) {
+        match self.render_style {
+            Some(RenderStyle::Classic) => {
+                self.color = ColorScheme::White.color();
+                self.paddle_colors = None;
+                self.offset_net = true;
+                self.score_flash = false;
+                self.paddle_hit_flash = false;
+                self.ball_spin = false;
+            }
+            Some(RenderStyle::Enhanced) => {
+                self.color = ColorScheme::Amber.color();
+                self.set_side_paddle_colors();
+                self.offset_net = false;
+                self.score_flash = true;
+                self.paddle_hit_flash = true;
+                self.ball_spin = true;
+            }
+            None => {}
+        }
+    }
+
+    // index into speed_tier_xvels for a rally of hit_counter returns
+    fn speed_tier(&self, hit_counter: i32) -> usize {
+        self.speed_tier_hits.iter().filter(|&&hits| hit_counter >= hits).count()
+    }
+
+    // (up, down) input actions for the paddle on the given side of the field
+    fn paddle_actions(&self, side: &PlayerSide) -> (&'static str, &'static str) {
+        match (side, self.mirror_controls) {
+            (PlayerSide::Left, false) | (PlayerSide::Right, true) => ("up_l", "dn_l"),
+            (PlayerSide::Right, false) | (PlayerSide::Left, true) => ("up_r", "dn_r"),
+        }
+    }
+
+    // follows the paddle keys when the controls are mirrored
+    fn turbo_action(&self, side: &PlayerSide) -> &'static str {
+        match (side, self.mirror_controls) {
+            (PlayerSide::Left, false) | (PlayerSide::Right, true) => "turbo_l",
+            (PlayerSide::Right, false) | (PlayerSide::Left, true) => "turbo_r",
+        }
+    }
+
+    fn paddle_color(&self, side: &PlayerSide) -> Color {
+        match (self.paddle_colors, side) {
+            (Some([left, _]), PlayerSide::Left) => left,
+            (Some([_, right]), PlayerSide::Right) => right,
+            (None, _) => self.color,
+        }
+    }
+}
+
+fn config() -> Gd<GameConfig> {
+    Engine::singleton()
+        .get_singleton("GameConfig".into())
+        .expect("GameConfig singleton is not registered")
+        .cast::<GameConfig>()
+}
+
+// every notable thing that happens in a game is reported through here, so a
+// single handler connected to game_event can log or record a whole game
+// kind is one of "serve", "paddle_hit" (segment index), "scored" (side that scored),
+// "bounce" ("ceiling" or "floor") or "game_over" (winning side)
+#[derive(GodotClass)]
+#[class(init, base=Object)]
+struct GameEvents {
+    base: Base<Object>
+}
+
+#[godot_api]
+impl GameEvents {
+    #[signal]
+    fn game_event(kind: GString, detail: Variant, time_msec: i64);
+}
+
+// the signal is deferred so handlers never run while the reporting node is still bound
+fn emit_game_event(kind: &str, detail: Variant) {
+    let Some(mut events) = Engine::singleton().get_singleton("GameEvents".into()) else { return };
+    let time_msec = Time::singleton().get_ticks_msec() as i64;
+    events.call_deferred("emit_signal".into(), &[
+        Variant::from("game_event"),
+        Variant::from(kind),
+        detail,
+        time_msec.to_variant(),
+    ]);
+}
+
+// the delta physics should advance by this tick, if at all
+// physics runs in physics_process at a fixed tick rate, so in frame step mode a step is one tick
+fn physics_delta(delta: f64) -> Option<f64> {
+    let config = config();
+    let config = config.bind();
+    if !config.frame_step || config.step_requested {
+        Some(delta)
+    } else {
+        None
+    }
+}
+
+// saved states can come from replay files, so a value of the wrong type keeps the current one
+// rather than panicking, missing keys are expected and skipped quietly
+fn state_value<T: FromGodot>(state: &Dictionary, key: &str, current: T) -> T {
+    let Some(value) = state.get(GString::from(key)) else { return current };
+    match value.try_to() {
+        Ok(value) => value,
+        Err(_) => {
+            godot_warn!("saved {key} has the wrong type {:?}, keeping the current value", value.get_type());
+            current
+        }
+    }
+}
+
+// JSON has no ints, so a state saved through it comes back with whole number floats instead
+fn state_int(state: &Dictionary, key: &str, current: i32) -> i32 {
+    let whole_float = state.get(GString::from(key))
+        .and_then(|value| value.try_to::<f64>().ok())
+        .filter(|float| float.fract() == 0.0);
+    match whole_float {
+        Some(float) => float as i32,
+        None => state_value(state, key, current),
+    }
+}
+
+// the original circuitry resulted in the net being shifted to the left instead
+// we can add HSHIFT to center everything, or we can turn it off for 'accuracy'
+// the blanking lengths are passed in rather than read here, see GameConfig::blanking
+fn hclk_to_xpos(hclk: i32, hblank: i32) -> f32 {
+    let hclk_since_hblank = hclk - hblank + HSHIFT;
+    hclk_since_hblank as f32 * PX_UNIT_WIDTH
+}
+
+// position as the original circuitry drew it, without the HSHIFT correction
+fn hclk_to_xpos_unshifted(hclk: i32, hblank: i32) -> f32 {
+    (hclk - hblank) as f32 * PX_UNIT_WIDTH
+}
+
+fn hclk_to_px(hclk: i32) -> i32 {
+    (hclk as f32 * PX_UNIT_WIDTH) as i32
+}
+
+fn vclk_to_ypos(vclk: i32, vblank: i32) -> f32 {
+    let vclk_since_vblank = vclk - vblank;
+    vclk_since_vblank as f32 * PX_UNIT_HEIGHT
+}
+
+fn vclk_to_px(vclk: i32) -> i32 {
+    (vclk as f32 * PX_UNIT_HEIGHT) as i32
+}
+
+#[derive(GodotClass)]
+#[class(base=Node)]
+struct Main {
+    paddle_l: Gd<Paddle>,
+    paddle_r: Gd<Paddle>,
+    // one ball unless multiball is on, see GameConfig::ball_count
+    balls: Vec<Gd<Ball>>,
+    // None on the side a TrainingWall stands in for, see GameConfig::training_wall
+    wall_l: Option<Gd<Wall>>,
+    wall_r: Option<Gd<Wall>>,
+    display: Gd<ScoreDisplay>,
+    speed_hud: Gd<SpeedHud>,
+    show_speed_hud: bool,
+    perf_hud: Gd<PerfHud>,
+    show_perf_hud: bool,
+    blanking_overlay: Gd<BlankingOverlay>,
+    show_blanking_overlay: bool,
+    games_won: [i32; 2],
+    attract_mode: bool,
+    // view only, the game itself always runs in field coordinates
+    camera: Gd<Camera2D>,
+    follow_ball: bool,
+    camera_zoom: f32,
+    // only resume on focus gain if it was focus loss that paused the game
+    paused_by_focus_loss: bool,
+    // seconds since the last input event, and whether any arrived since the game started
+    idle_time: f64,
+    input_this_game: bool,
+    // the game is added here instead of to Main when rendering at a set resolution
+    field_container: Option<Gd<SubViewportContainer>>,
+    field: Option<Gd<SubViewport>>,
+    // refreshed every frame for get_observation
+    observation: Dictionary,
+    // a MatchOverScreen is up, see on_game_over
+    awaiting_rematch: bool,
+    // one serialize_state per tick while recording, and the frames being played back if any
+    recording: bool,
+    replay_frames: VariantArray,
+    playback_frames: VariantArray,
+    playback_index: Option<usize>,
+    base: Base<Node>
+}
+
+#[godot_api]
+impl INode for Main {
+    fn init(base: Base<Node>) -> Self {
+        Self {
+            paddle_l: Paddle::from_side(PlayerSide::Left, PADDLE_START_VCLK),
+            paddle_r: Paddle::from_side(PlayerSide::Right, PADDLE_START_VCLK),
+            balls: Vec::new(),
+            wall_l: None,
+            wall_r: None,
+            display: ScoreDisplay::new_alloc(),
+            speed_hud: SpeedHud::new_alloc(),
+            show_speed_hud: false,
+            perf_hud: PerfHud::new_alloc(),
+            show_perf_hud: false,
+            blanking_overlay: BlankingOverlay::new_alloc(),
+            show_blanking_overlay: false,
+            games_won: [0, 0],
+            attract_mode: false,
+            camera: Camera2D::new_alloc(),
+            follow_ball: false,
+            camera_zoom: 1.0,
+            paused_by_focus_loss: false,
+            idle_time: 0.0,
+            input_this_game: false,
+            field_container: None,
+            field: None,
+            observation: Dictionary::new(),
+            awaiting_rematch: false,
+            recording: false,
+            replay_frames: VariantArray::new(),
+            playback_frames: VariantArray::new(),
+            playback_index: None,
+            base
+        } 
+    }
+
+    fn input(&mut self, _event: Gd<InputEvent>) {
+        self.idle_time = 0.0;
+        self.input_this_game = true;
+    }
+
+    fn on_notification(&mut self, what: NodeNotification) {
+        match what {
+            NodeNotification::APPLICATION_FOCUS_OUT => {
+                if config().bind().pause_on_focus_loss && !self.is_paused() {
+                    self.set_paused(true);
+                    self.paused_by_focus_loss = true;
+                }
+            }
+            NodeNotification::APPLICATION_FOCUS_IN => {
+                if self.paused_by_focus_loss {
+                    self.set_paused(false);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn process(&mut self, delta: f64) {
+        self.update_observation();
+        self.update_camera(delta);
+        self.check_idle(delta);
+        let input = Input::singleton();
+        if input.is_action_pressed(START_ACTION.into()) {
+            if self.attract_mode {
+                self.attract_mode = false;
+                self.games_won = [0, 0];
+                self.new_game();
+            }
+        }
+        if input.is_action_just_pressed("restart".into()) {
+            self.restart_game();
+        }
+        if input.is_action_just_pressed("debug_speed".into()) {
+            self.show_speed_hud = !self.show_speed_hud;
+            let show_speed_hud = self.show_speed_hud;
+            self.speed_hud.set_visible(show_speed_hud);
+        }
+        if input.is_action_just_pressed("debug_perf".into()) {
+            self.show_perf_hud = !self.show_perf_hud;
+            let show_perf_hud = self.show_perf_hud;
+            self.perf_hud.set_visible(show_perf_hud);
+        }
+        if input.is_action_just_pressed("debug_blanking".into()) {
+            self.show_blanking_overlay = !self.show_blanking_overlay;
+            let show_blanking_overlay = self.show_blanking_overlay;
+            self.blanking_overlay.set_visible(show_blanking_overlay);
+        }
+        if input.is_action_just_pressed("debug_segments".into()) {
+            let mut config = config();
+            let show_collision_segments = config.bind().show_collision_segments;
+            config.bind_mut().show_collision_segments = !show_collision_segments;
+        }
+        if input.is_action_just_pressed("debug_frame_step".into()) {
+            let mut config = config();
+            let frame_step = config.bind().frame_step;
+            config.bind_mut().frame_step = !frame_step;
+        }
+    }
+
+    // main is processed before its children, so they see this tick's step request
+    fn physics_process(&mut self, _delta: f64) {
+        let step_requested = Input::singleton().is_action_just_pressed("debug_step".into());
+        config().bind_mut().step_requested = step_requested;
+        self.play_back_frame();
+        if self.recording {
+            let state = self.serialize_state();
+            self.replay_frames.push(state.to_variant());
+        }
+    }
+
+    fn ready(&mut self) {
+        config().bind_mut().load_settings();
+        let tick_rate = config().bind().tick_rate;
+        Engine::singleton().set_physics_ticks_per_second(tick_rate);
+        let callable = self.base().callable("on_window_size_changed");
+        if let Some(mut viewport) = self.base().get_viewport() {
+            viewport.connect("size_changed".into(), callable);
+        }
+        self.new_game();
+    }
+}
+
+#[godot_api]
+impl Main {
+    fn clear_children(&mut self) {
+        let children = match &self.field {
+            Some(field) => field.get_children().iter_shared().collect::<Vec<_>>(),
+            None => self.base().get_children().iter_shared().collect(),
+        };
+        for mut child in children {
+            child.queue_free();
+        }
+    }
+
+    fn add_to_field(&mut self, node: Gd<Node>) {
+        match self.field.clone() {
+            Some(mut field) => field.add_child(node),
+            None => self.base_mut().add_child(node),
+        }
+    }
+
+    // the field keeps its 640x480 coordinates and is drawn into a viewport of the configured
+    // size, which is then scaled up by a whole number with no filtering so the pixels stay crisp
+    fn apply_render_resolution(&mut self) {
+        let resolution = config().bind().render_resolution;
+        match (resolution, self.field.clone()) {
+            (Some(size), Some(mut field)) => field.set_size(size),
+            (Some(size), None) => {
+                let mut field = SubViewport::new_alloc();
+                field.set_size(size);
+                field.set_size_2d_override(Vector2i::new(VIEWPORT_WIDTH, VIEWPORT_HEIGHT));
+                field.set_size_2d_override_stretch(true);
+                let mut container = SubViewportContainer::new_alloc();
+                container.set_texture_filter(TextureFilter::NEAREST);
+                container.add_child(field.clone().upcast());
+                self.base_mut().add_child(container.clone().upcast());
+                self.base_mut().move_child(container.clone().upcast(), 0);
+                self.field_container = Some(container);
+                self.field = Some(field);
+            }
+            (None, Some(_)) => {
+                if let Some(mut container) = self.field_container.take() {
+                    container.queue_free();
+                }
+                self.field = None;
+            }
+            (None, None) => {}
+        }
+        self.on_window_size_changed();
+    }
+
+    #[func]
+    fn on_window_size_changed(&mut self) {
+        let (Some(mut container), Some(field)) = (self.field_container.clone(), self.field.clone()) else {
+            return
+        };
+        let Some(viewport) = self.base().get_viewport() else {
+            return
+        };
+        let window = viewport.get_visible_rect().size;
+        let size = field.get_size();
+        let size = Vector2::new(size.x as f32, size.y as f32);
+        let scale = (window.x / size.x).min(window.y / size.y).floor().max(1.0);
+        container.set_size(size);
+        container.set_scale(Vector2::new(scale, scale));
+        container.set_position((window - size * scale) / 2.0);
+    }
+
+    // the old nodes are only queued for deletion by clear_children, so they could still
+    // fire into Main for the rest of the frame unless they are disconnected first
+    fn disconnect_game_signals(&mut self) {
+        if !self.display.is_instance_valid() {
+            return
+        }
+        for (mut source, signal, callable) in self.game_connections() {
+            if source.is_connected(signal.into(), callable.clone()) {
+                source.disconnect(signal.into(), callable);
+            }
+        }
+    }
+
+    // every connection new_game makes between the current game's nodes and Main
+    fn game_connections(&self) -> Vec<(Gd<Object>, &'static str, Callable)> {
+        let display_callable = self.display.callable("on_side_scored");
+        let mut connections: Vec<(Gd<Object>, &'static str, Callable)> = vec![
+            (self.display.clone().upcast(), "score_updated", self.base().callable("on_ball_out")),
+            (self.display.clone().upcast(), "score_updated", self.base().callable("on_score_updated")),
+            (self.display.clone().upcast(), "game_over", self.base().callable("on_game_over")),
+        ];
+        for wall in self.wall_l.iter().chain(self.wall_r.iter()) {
+            if wall.is_instance_valid() {
+                connections.push((wall.clone().upcast(), "scored", display_callable.clone()));
+            }
+        }
+        connections
+    }
+
+    // how many times callable is connected to signal, anything but 1 after new_game means a restart
+    // left the last game's connections behind or missed one
+    fn connection_count(source: &Gd<Object>, signal: &str, callable: &Callable) -> usize {
+        source.get_signal_connection_list(signal.into())
+            .iter_shared()
+            .filter(|connection| {
+                connection.get(GString::from("callable"))
+                    .and_then(|value| value.try_to::<Callable>().ok())
+                    .is_some_and(|connected| connected == *callable)
+            })
+            .count()
+    }
+
+    #[func]
+    fn new_game(&mut self) {
+        config().bind_mut().apply_render_style();
+        self.idle_time = 0.0;
+        self.input_this_game = false;
+        self.awaiting_rematch = false;
+        self.disconnect_game_signals();
+        self.clear_children();
+        self.apply_render_resolution();
+        let paddle_start_vclk = config().bind().paddle_start_vclk;
+        self.paddle_l = Paddle::from_side(PlayerSide::Left, paddle_start_vclk);
+        self.paddle_r = Paddle::from_side(PlayerSide::Right, paddle_start_vclk);
+        let ball_count = config().bind().ball_count;
+        self.balls = (0..ball_count).map(Ball::from_index).collect();
+        // only the walls that go in the tree are made, anything else would never be freed
+        let training_wall = config().bind().training_wall.clone();
+        let new_wall = |side: PlayerSide| {
+            let mut wall = Wall::new_alloc();
+            wall.bind_mut().set_side(side);
+            wall
+        };
+        self.wall_l = (training_wall != Some(PlayerSide::Left)).then(|| new_wall(PlayerSide::Left));
+        self.wall_r = (training_wall != Some(PlayerSide::Right)).then(|| new_wall(PlayerSide::Right));
+        let mut display = ScoreDisplay::new_alloc();
+        display.bind_mut().games_won = self.games_won;
+        self.display = display.clone();
+        if let Some(difficulty) = config().bind().ai_difficulty {
+            let balls = self.balls.clone();
+            self.paddle_r.bind_mut().enable_ai(balls, difficulty);
+        }
+
+        // every signal is connected before anything enters the tree, since a child's ready
+        // can already start emitting, e.g. the balls serve as soon as they are added
+        let display_callable = display.callable("on_side_scored");
+        for wall in self.wall_l.iter_mut().chain(self.wall_r.iter_mut()) {
+            wall.connect("scored".into(), display_callable.clone());
+        }
+        display.connect("score_updated".into(), self.base().callable("on_ball_out"));
+        display.connect("score_updated".into(), self.base().callable("on_score_updated"));
+        display.connect("game_over".into(), self.base().callable("on_game_over"));
+        debug_assert!(
+            self.game_connections().iter()
+                .all(|(source, signal, callable)| Main::connection_count(source, signal, callable) == 1),
+            "every game signal should be connected exactly once",
+        );
+
+        if config().bind().show_net {
+            self.add_to_field(Net::new_alloc().upcast());
+        }
+        let paddle_l = self.paddle_l.clone();
+        let paddle_r = self.paddle_r.clone();
+        self.add_to_field(paddle_l.upcast());
+        self.add_to_field(paddle_r.upcast());
+        let walls = [self.wall_l.clone(), self.wall_r.clone()];
+        for (wall, side) in iter::zip(walls, [PlayerSide::Left, PlayerSide::Right]) {
+            match wall {
+                Some(wall) => self.add_to_field(wall.upcast()),
+                None => self.add_to_field(TrainingWall::from_side(side).upcast()),
+            }
+        }
+        self.add_to_field(VBounds::new_alloc().upcast());
+        self.add_to_field(display.upcast());
+        let mut pause_menu = PauseMenu::new_alloc();
+        pause_menu.connect("pause_requested".into(), self.base().callable("set_paused"));
+        pause_menu.connect("restart_requested".into(), self.base().callable("restart_game"));
+        self.add_to_field(pause_menu.upcast());
+        // last, so the opening serve happens with the rest of the field in place
+        for ball in self.balls.clone() {
+            self.add_to_field(ball.upcast());
+        }
+
+        self.speed_hud = SpeedHud::from_ball(self.balls[0].clone());
+        let show_speed_hud = self.show_speed_hud;
+        self.speed_hud.set_visible(show_speed_hud);
+        let speed_hud = self.speed_hud.clone();
+        self.add_to_field(speed_hud.upcast());
+
+        self.perf_hud = PerfHud::new_alloc();
+        let show_perf_hud = self.show_perf_hud;
+        self.perf_hud.set_visible(show_perf_hud);
+        let perf_hud = self.perf_hud.clone();
+        self.add_to_field(perf_hud.upcast());
+
+        self.blanking_overlay = BlankingOverlay::new_alloc();
+        let show_blanking_overlay = self.show_blanking_overlay;
+        self.blanking_overlay.set_visible(show_blanking_overlay);
+        let blanking_overlay = self.blanking_overlay.clone();
+        self.add_to_field(blanking_overlay.upcast());
+
+        if config().bind().controls_hint {
+            self.add_to_field(ControlsHint::new_alloc().upcast());
+        }
+
+        if config().bind().serve_lock {
+            for ball in self.balls.clone() {
+                self.add_to_field(ServePrompt::from_ball(ball).upcast());
+            }
+        }
+
+        if config().bind().practice_mode {
+            let practice_aim = PracticeAim::from_ball(self.balls[0].clone());
+            self.add_to_field(practice_aim.upcast());
+        }
+
+        self.camera = Camera2D::new_alloc();
+        let camera = self.camera.clone();
+        self.add_to_field(camera.upcast());
+        self.camera.make_current();
+        self.update_camera(0.0);
+    }
+
+    // with follow_ball off the camera stays centered on the field
+    #[func]
+    fn set_follow_ball(&mut self, enabled: bool) {
+        self.follow_ball = enabled;
+    }
+
+    // 1.0 shows the whole field, zooming out past it isn't allowed
+    #[func]
+    fn set_zoom(&mut self, zoom: f32) {
+        self.camera_zoom = zoom.max(1.0);
+    }
+
+    // eases towards the ball when following it, but never shows anything outside the field
+    fn update_camera(&mut self, delta: f64) {
+        if !self.camera.is_instance_valid() {
+            return
+        }
+        let field = self.camera.get_viewport_rect().size;
+        let zoom = self.camera_zoom;
+        let mut target = field / 2.0;
+        let followed = self.balls.first().filter(|ball| ball.is_instance_valid());
+        if let (true, Some(ball)) = (self.follow_ball, followed) {
+            let half_view = field / (2.0 * zoom);
+            let ball_pos = ball.bind().pos;
+            target.x = ball_pos.x.clamp(half_view.x, field.x - half_view.x);
+            target.y = ball_pos.y.clamp(half_view.y, field.y - half_view.y);
+        }
+        let weight = if delta > 0.0 { (CAMERA_FOLLOW_RATE * delta as f32).min(1.0) } else { 1.0 };
+        let pos = self.camera.get_position().lerp(target, weight);
+        self.camera.set_position(pos);
+        self.camera.set_zoom(Vector2::new(zoom, zoom));
+    }
+
+    // in multiball the balls that have gone out wait for the rest, and they are all served together
+    #[func]
+    fn on_ball_out(&mut self) {
+        let any_in_play = self.balls.iter().any(|ball| ball.is_instance_valid() && ball.bind().in_play);
+        if any_in_play {
+            return
+        }
+        for ball in self.balls.iter_mut() {
+            ball.bind_mut().on_score_updated();
+        }
+    }
+
+    // briefly flash the whole field when a point is scored
+    #[func]
+    fn on_score_updated(&mut self) {
+        if !config().bind().score_flash {
+            return
+        }
+        let mut flash = ColorRect::new_alloc();
+        let mut color = config().bind().color;
+        color.a = 0.5;
+        flash.set_color(color);
+        flash.set_size(Vector2::new(VIEWPORT_WIDTH as f32, VIEWPORT_HEIGHT as f32));
+        self.add_to_field(flash.clone().upcast());
+        if let Some(mut tree) = self.base().get_tree() {
+            if let Some(mut timer) = tree.create_timer(SCORE_FLASH_DURATION) {
+                timer.connect("timeout".into(), flash.callable("queue_free"));
+            }
+        }
+    }
+
+    #[signal]
+    fn match_over(winner: GString);
+
+    // a match is over once a player has won the majority of its games
+    // otherwise the next game starts straight away, carrying over the games won
+    #[func]
+    fn on_game_over(&mut self, winner: GString) {
+        let Some(winner_side) = PlayerSide::from_name(&winner.to_string()) else {
+            godot_warn!("game won by unknown side {winner}");
+            return
+        };
+        let winner_index = winner_side.index();
+        self.games_won[winner_index] += 1;
+        let games_to_win = config().bind().match_length / 2 + 1;
+        if self.games_won[winner_index] >= games_to_win {
+            self.base_mut().emit_signal("match_over".into(), &[winner.to_variant()]);
+            // the final score stays up on the display underneath, with the balls held where they
+            // went out so nothing is served or scored until a rematch or attract mode
+            self.awaiting_rematch = true;
+            for ball in self.balls.iter_mut().filter(|ball| ball.is_instance_valid()) {
+                ball.bind_mut().frozen = true;
+            }
+            let mut screen = MatchOverScreen::from_winner(winner_side);
+            screen.connect("rematch".into(), self.base().callable("on_rematch"));
+            screen.connect("timed_out".into(), self.base().callable("attract_mode"));
+            self.add_to_field(screen.upcast());
+        } else {
+            self.base_mut().call_deferred("new_game".into(), &[]);
+        }
+    }
+
+    // like a cabinet left alone, fall back to attract mode if nobody has touched a game that
+    // hasn't got going yet, either because nobody has played at all or the ball is waiting to be served
+    fn check_idle(&mut self, delta: f64) {
+        let Some(timeout) = config().bind().attract_timeout else { return };
+        if self.attract_mode || self.awaiting_rematch {
+            return
+        }
+        self.idle_time += delta;
+        let waiting_for_serve = self.balls.iter().any(|ball| ball.is_instance_valid() && ball.bind().parked);
+        let fresh = !self.input_this_game || waiting_for_serve;
+        if fresh && self.idle_time >= timeout {
+            self.attract_mode();
+        }
+    }
+
+    // pauses the whole scene tree, notifications like focus changes still arrive while paused
+    // the pause menu pauses through here as well, so opening or closing it takes over from a focus loss pause
+    #[func]
+    fn set_paused(&mut self, paused: bool) {
+        self.paused_by_focus_loss = false;
+        if let Some(mut tree) = self.base().get_tree() {
+            tree.set_pause(paused);
+        }
+    }
+
+    #[func]
+    fn is_paused(&self) -> bool {
+        self.base().get_tree().is_some_and(|tree| tree.is_paused())
+    }
+
+    // everything needed to carry on a game exactly where it left off, see load_state
+    #[func]
+    fn serialize_state(&self) -> Dictionary {
+        let mut state = Dictionary::new();
+        if self.display.is_instance_valid() {
+            let [score_l, score_r] = self.display.bind().score;
+            state.set(GString::from("score_l"), score_l);
+            state.set(GString::from("score_r"), score_r);
+        }
+        state.set(GString::from("games_won_l"), self.games_won[0]);
+        state.set(GString::from("games_won_r"), self.games_won[1]);
+        // only the first ball in multiball
+        if let Some(ball) = self.balls.first().filter(|ball| ball.is_instance_valid()) {
+            let ball = ball.bind();
+            state.set(GString::from("ball_pos"), ball.pos);
+            state.set(GString::from("ball_xvel"), ball.xvel);
+            state.set(GString::from("ball_yvel"), ball.yvel);
+            state.set(GString::from("hit_counter"), ball.hit_counter);
+            state.set(GString::from("has_collided"), ball.has_collided);
+            state.set(GString::from("lob_time_left"), ball.lob_time_left);
+            state.set(GString::from("serve_pending"), ball.serve_pending);
+            state.set(GString::from("parked"), ball.parked);
+            state.set(GString::from("frozen"), ball.frozen);
+        }
+        if self.paddle_l.is_instance_valid() {
+            state.set(GString::from("paddle_l_y"), self.paddle_l.bind().ypos);
+        }
+        if self.paddle_r.is_instance_valid() {
+            state.set(GString::from("paddle_r_y"), self.paddle_r.bind().ypos);
+        }
+        state
+    }
+
+    // starts a new game and puts it into the saved state, missing keys keep the new game's values
+    // a pending serve can't be resumed part way through, so its timer starts again from the beginning
+    #[func]
+    fn load_state(&mut self, state: Dictionary) {
+        self.games_won = [
+            state_int(&state, "games_won_l", self.games_won[0]),
+            state_int(&state, "games_won_r", self.games_won[1]),
+        ];
+        self.attract_mode = false;
+        self.new_game();
+        self.apply_state(&state);
+    }
+
+    // the part of load_state that puts the current game's nodes into the saved state
+    fn apply_state(&mut self, state: &Dictionary) {
+        {
+            let mut display = self.display.bind_mut();
+            let [score_l, score_r] = display.score;
+            display.score = [
+                state_int(state, "score_l", score_l),
+                state_int(state, "score_r", score_r),
+            ];
+        }
+        let mut first_ball = self.balls[0].clone();
+        let serve_pending = {
+            let mut ball = first_ball.bind_mut();
+            ball.pos = state_value(state, "ball_pos", ball.pos);
+            ball.xvel = state_int(state, "ball_xvel", ball.xvel);
+            ball.yvel = state_int(state, "ball_yvel", ball.yvel);
+            ball.hit_counter = state_int(state, "hit_counter", ball.hit_counter);
+            ball.has_collided = state_value(state, "has_collided", ball.has_collided);
+            ball.lob_time_left = state_value(state, "lob_time_left", ball.lob_time_left);
+            ball.parked = state_value(state, "parked", ball.parked);
+            ball.frozen = state_value(state, "frozen", ball.frozen);
+            state_value(state, "serve_pending", false)
+        };
+        // a ball frozen for playback doesn't move itself, so it is put in place here
+        let pos = first_ball.bind().pos;
+        first_ball.set_global_position(pos);
+        if serve_pending {
+            first_ball.bind_mut().in_play = false;
+            first_ball.bind_mut().on_score_updated();
+        }
+        let mut paddle_l = self.paddle_l.bind_mut();
+        paddle_l.ypos = state_value(state, "paddle_l_y", paddle_l.ypos);
+        drop(paddle_l);
+        let mut paddle_r = self.paddle_r.bind_mut();
+        paddle_r.ypos = state_value(state, "paddle_r_y", paddle_r.ypos);
+    }
+
+    // drops anything recorded so far
+    #[func]
+    fn start_recording(&mut self) {
+        self.replay_frames = VariantArray::new();
+        self.recording = true;
+    }
+
+    #[func]
+    fn stop_recording(&mut self) {
+        self.recording = false;
+    }
+
+    // the header is REPLAY_MAGIC and REPLAY_VERSION as two 32 bit ints, followed by the
+    // recorded frames as one array of serialize_state dictionaries in godot's binary variant format
+    #[func]
+    fn export_replay(&mut self, path: GString) -> bool {
+        let Some(mut file) = FileAccess::open(path.clone(), ModeFlags::WRITE) else {
+            godot_warn!("couldn't open {path} to write a replay");
+            return false
+        };
+        file.store_32(REPLAY_MAGIC);
+        file.store_32(REPLAY_VERSION);
+        file.store_var(self.replay_frames.to_variant());
+        true
+    }
+
+    // the first frame goes through load_state to set up a fresh game, the rest are applied
+    // over it one per tick, recording stops so the replay isn't recorded on top of itself
+    #[func]
+    fn import_replay(&mut self, path: GString) -> bool {
+        let Some(mut file) = FileAccess::open(path.clone(), ModeFlags::READ) else {
+            godot_warn!("couldn't open replay {path}");
+            return false
+        };
+        if file.get_32() != REPLAY_MAGIC {
+            godot_warn!("{path} isn't a replay");
+            return false
+        }
+        let version = file.get_32();
+        if version > REPLAY_VERSION {
+            godot_warn!("{path} is replay version {version}, only up to {REPLAY_VERSION} is supported");
+            return false
+        }
+        let Ok(frames) = file.get_var().try_to::<VariantArray>() else {
+            godot_warn!("{path} has no frames");
+            return false
+        };
+        self.recording = false;
+        self.playback_frames = frames;
+        self.playback_index = Some(0);
+        true
+    }
+
+    // the balls are frozen for as long as playback runs, so the live simulation can't move them
+    // between frames or score with them, the game carries on from the last frame once it's over
+    fn play_back_frame(&mut self) {
+        let Some(index) = self.playback_index else { return };
+        if index >= self.playback_frames.len() {
+            self.playback_index = None;
+            self.set_balls_frozen(false);
+            return
+        }
+        let Ok(frame) = self.playback_frames.get(index).try_to::<Dictionary>() else {
+            godot_warn!("replay frame {index} isn't a saved state, stopping playback");
+            self.playback_index = None;
+            return
+        };
+        if index == 0 {
+            self.load_state(frame);
+        } else {
+            self.apply_state(&frame);
+        }
+        // applying a frame can queue or run a serve, which unfreezes the ball
+        self.set_balls_frozen(true);
+        self.playback_index = Some(index + 1);
+    }
+
+    fn set_balls_frozen(&mut self, frozen: bool) {
+        for ball in self.balls.iter_mut().filter(|ball| ball.is_instance_valid()) {
+            ball.bind_mut().frozen = frozen;
+        }
+    }
+
+    // what an external agent can see of the game, read with get_observation and act with Paddle::set_target_y
+    // the same dictionary is refilled in place every frame rather than building a new one per call
+    fn update_observation(&mut self) {
+        let observation = &mut self.observation;
+        observation.clear();
+        if let Some(ball) = self.balls.first().filter(|ball| ball.is_instance_valid()) {
+            let ball = ball.bind();
+            observation.set(GString::from("ball_pos"), ball.pos);
+            observation.set(GString::from("ball_vel"), Vector2i::new(ball.xvel, ball.yvel));
+        }
+        if self.paddle_l.is_instance_valid() {
+            observation.set(GString::from("paddle_l_y"), self.paddle_l.bind().ypos);
+        }
+        if self.paddle_r.is_instance_valid() {
+            observation.set(GString::from("paddle_r_y"), self.paddle_r.bind().ypos);
+        }
+        if self.display.is_instance_valid() {
+            let [score_l, score_r] = self.display.bind().score;
+            observation.set(GString::from("score_l"), score_l);
+            observation.set(GString::from("score_r"), score_r);
+        }
+    }
+
+    // ball_pos, ball_vel (clocks per frame), paddle_l_y, paddle_r_y, score_l and score_r as of
+    // this frame, keys are left out while their node doesn't exist e.g. the paddles in attract mode
+    // this is shared with Main, anything written to it is thrown away on the next frame
+    #[func]
+    fn get_observation(&self) -> Dictionary {
+        self.observation.clone()
+    }
+
+    // 0 is left, 1 is right, None once the paddle is gone e.g. in attract mode
+    #[func]
+    fn get_paddle(&self, side: i32) -> Option<Gd<Paddle>> {
+        let paddle = match PlayerSide::from_index(side)? {
+            PlayerSide::Left => self.paddle_l.clone(),
+            PlayerSide::Right => self.paddle_r.clone(),
+        };
+        paddle.is_instance_valid().then_some(paddle)
+    }
+
+    // starts the current game over from 0-0, games already won in the match are kept
+    // new_game recreates every child, so the signals are connected fresh as well
+    #[func]
+    fn restart_game(&mut self) {
+        self.attract_mode = false;
+        self.new_game();
+    }
+
+    #[func]
+    fn on_rematch(&mut self) {
+        self.games_won = [0, 0];
+        self.restart_game();
+    }
+
+    #[func]
+    fn attract_mode(&mut self) {
+        self.attract_mode = true;
+        self.awaiting_rematch = false;
+        self.paddle_l.queue_free();
+        self.paddle_r.queue_free();
+        for wall in self.wall_l.iter_mut().chain(self.wall_r.iter_mut()) {
+            wall.bind_mut().attract_mode = true;
+        }
+        // nobody is around to serve in attract mode
+        for ball in self.balls.iter_mut() {
+            ball.bind_mut().serve_lock = false;
+            ball.bind_mut().serve();
+        }
+        // freed along with everything else when the next game starts
+        self.add_to_field(StartPrompt::new_alloc().upcast());
+        if config().bind().controls_hint {
+            self.add_to_field(ControlsHint::new_alloc().upcast());
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Rect<T> {
+    x: T,
+    y: T,
+    w: T,
+    h: T,
+}
+
+impl Into<Rect<f32>> for Rect<i32> {
+    fn into(self) -> Rect<f32> {
+        Rect {
+            x: self.x as f32,
+            y: self.y as f32,
+            w: self.w as f32,
+            h: self.h as f32,
+        }
+    }
+}
+
+impl<T> Rect<T> {
+    fn new(x: T, y: T, w: T, h: T) -> Self {
+        Self { x, y, w, h }
+    }
+
+    fn from_clk(hclk: i32, vclk: i32, w: i32, h: i32, blanking: (i32, i32)) -> Rect::<i32> {
+        Rect::<i32> {
+            x: hclk_to_xpos(hclk, blanking.0) as i32,
+            y: vclk_to_ypos(vclk, blanking.1) as i32,
+            w: hclk_to_px(w),
+            h: vclk_to_px(h),
+        }
+    }
+}
+
+// axis-aligned checks for when we can't lean on godot's physics
+// edges are half-open, so rects that only touch don't intersect
+impl Rect<f32> {
+    fn intersects(&self, other: &Rect<f32>) -> bool {
+        self.x < other.x + other.w && other.x < self.x + self.w
+            && self.y < other.y + other.h && other.y < self.y + self.h
+    }
+
+    fn contains_point(&self, p: Vector2) -> bool {
+        (self.x..self.x + self.w).contains(&p.x) && (self.y..self.y + self.h).contains(&p.y)
+    }
+}
+
+// the corners of a rect in clockwise order, one quad of a polygon
+fn rect_vertices(rect: &Rect<i32>) -> [Vector2; 4] {
+    let rect_f: Rect<f32> = rect.clone().into();
+    [
+        Vector2::new(rect_f.x, rect_f.y),
+        Vector2::new(rect_f.x+rect_f.w, rect_f.y),
+        Vector2::new(rect_f.x+rect_f.w, rect_f.y+rect_f.h),
+        Vector2::new(rect_f.x, rect_f.y+rect_f.h),
+    ]
+}
+
+fn set_vertices_from_rect(vertices: &mut PackedVector2Array, rect: &Rect<i32>) {
+    for vertex in rect_vertices(rect) {
+        vertices.push(vertex);
+    }
+}
+
+trait AddRect {
+    fn add_rect(&mut self, rect: &Rect<i32>);
+}
+
+impl AddRect for Polygon2D {
+    fn add_rect(&mut self, rect: &Rect<i32>) {
+        let mut vertices = self.get_polygon();
+        set_vertices_from_rect(&mut vertices, rect);
+        self.set_polygon(vertices);
+    }
+}
+
+impl AddRect for CollisionPolygon2D {
+    fn add_rect(&mut self, rect: &Rect<i32>) {
+        let mut vertices = self.get_polygon();
+        set_vertices_from_rect(&mut vertices, rect);
+        self.set_polygon(vertices);
+    }
+}
+
+// physics layers, balls only look for surfaces and surfaces only look for balls
+// so paddles, walls and other balls never report overlapping each other
+const BALL_LAYER: u32 = 1 << 0;
+const SURFACE_LAYER: u32 = 1 << 1;
+
+trait CollisionLayers {
+    fn set_ball_layers(&mut self);
+    fn set_surface_layers(&mut self);
+}
+
+impl CollisionLayers for Area2D {
+    fn set_ball_layers(&mut self) {
+        self.set_collision_layer(BALL_LAYER);
+        self.set_collision_mask(SURFACE_LAYER);
+    }
+
+    fn set_surface_layers(&mut self) {
+        self.set_collision_layer(SURFACE_LAYER);
+        self.set_collision_mask(BALL_LAYER);
+    }
+}
+
+// a dark border just inside a width x height rect at the origin, so high contrast mode
+// can mark shapes apart by more than their color, like the hole cut into the ball
+// the line is centered on its points, so they are inset by half its width to keep it inside
+fn contrast_outline(width: i32, height: i32) -> Gd<Line2D> {
+    let line_width = 2.0;
+    let inset = line_width / 2.0;
+    let (width, height) = (width as f32, height as f32);
+    let mut outline = Line2D::new_alloc();
+    outline.set_points(PackedVector2Array::from(&[
+        Vector2::new(inset, inset),
+        Vector2::new(width - inset, inset),
+        Vector2::new(width - inset, height - inset),
+        Vector2::new(inset, height - inset),
+    ]));
+    outline.set_closed(true);
+    outline.set_width(line_width);
+    outline.set_default_color(Color::BLACK);
+    outline
+}
+
+// every quad pushed by set_vertices_from_rect is its own polygon
+// the nth quad is always [4n, 4n+1, 4n+2, 4n+3], so an index list is only ever extended
+fn extend_quad_indices(polygon_indices: &mut Array<Variant>, num_quads: usize) {
+    for i in polygon_indices.len()..num_quads {
+        let base = 4*i as i32;
+        polygon_indices.push(PackedInt32Array::from(&[base, base+1, base+2, base+3]).to_variant());
+    }
+}
+
+fn quad_indices(num_vertices: usize) -> Array<Variant> {
+    let mut polygon_indices = Array::<Variant>::new();
+    extend_quad_indices(&mut polygon_indices, num_vertices/4);
+    polygon_indices
+}
+
+// the score and net are redrawn with the same number of quads most of the time,
+// so the existing indices are kept and only grown or trimmed when the count changes
+fn polygon_set_indices(polygon: &mut Gd<Polygon2D>) {
+    let num_quads = polygon.get_polygon().len()/4;
+    let mut polygon_indices = polygon.get_polygons();
+    if polygon_indices.len() == num_quads {
+        return
+    }
+    if polygon_indices.len() > num_quads {
+        polygon_indices.resize(num_quads);
+    } else {
+        extend_quad_indices(&mut polygon_indices, num_quads);
+    }
+    polygon.set_polygons(polygon_indices);
+}
+
+// the net's segments for a field of the given height, kept apart from Net so the
+// vertices can be built (and timed, see PerfHud::benchmark_draw_path) without a scene
+fn net_vertices(vertices: &mut PackedVector2Array, net_left_edge: i32, field_height: i32) {
+    let net_segment_spacing: usize = vclk_to_px(8).try_into().unwrap();
+    let net_width = hclk_to_px(1);
+    let net_height = vclk_to_px(4);
+    for i in (0..field_height).step_by(net_segment_spacing) {
+        let rect = Rect::new(net_left_edge, i, net_width, net_height);
+        set_vertices_from_rect(vertices, &rect);
+    }
+}
+
+#[derive(GodotClass)]
+#[class(init, base=Polygon2D)]
+struct Net {
+    base: Base<Polygon2D>
+}
+
+#[godot_api]
+impl IPolygon2D for Net {
+    fn ready(&mut self) {
+        self.draw();
+        let callable = self.base().callable("on_viewport_size_changed");
+        if let Some(mut viewport) = self.base().get_viewport() {
+            viewport.connect("size_changed".into(), callable);
+        }
+        config().connect("config_changed".into(), self.base().callable("on_config_changed"));
+    }
+}
+
+#[godot_api]
+impl Net {
+    // the net is triggered at 256H from the HRST signal
+    // the net is dependent on a 4V signal for the segments, and is only one pulse wide
+    // this means the net should be drawn with roughly 2x8 segments 8px apart
+    // the segments run down the whole viewport, so this is redrawn whenever it is resized
+    // the net can be left off-center like on the real hardware, see GameConfig::offset_net
+    fn draw(&mut self) {
+        let hblank = config().bind().hblank;
+        let net_left_edge = if config().bind().offset_net {
+            hclk_to_xpos_unshifted(256, hblank) as i32
+        } else {
+            hclk_to_xpos(256, hblank) as i32
+        };
+        let field_height = self.base().get_viewport_rect().size.y as i32;
+        let mut vertices = PackedVector2Array::new();
+        net_vertices(&mut vertices, net_left_edge, field_height);
+        self.base_mut().set_polygon(vertices);
+        polygon_set_indices(&mut self.base_mut());
+        let color = config().bind().color;
+        self.base_mut().set_color(color);
+    }
+
+    #[func]
+    fn on_viewport_size_changed(&mut self) {
+        self.draw();
+    }
+
+    // the color or offset_net may have changed
+    #[func]
+    fn on_config_changed(&mut self) {
+        self.draw();
+    }
+}
+
+#[derive(Clone, PartialEq)]
+enum PlayerSide {
+    Left,
+    Right
+}
+
+impl PlayerSide {
+    // index into per-player arrays like the score
+    fn index(&self) -> usize {
+        match self {
+            PlayerSide::Left => 0,
+            PlayerSide::Right => 1,
+        }
+    }
+
+    fn opponent(&self) -> PlayerSide {
+        match self {
+            PlayerSide::Left => PlayerSide::Right,
+            PlayerSide::Right => PlayerSide::Left,
+        }
+    }
+
+    // the names used in signal payloads and game events
+    fn as_str(&self) -> &'static str {
+        match self {
+            PlayerSide::Left => "left",
+            PlayerSide::Right => "right",
+        }
+    }
+
+    fn from_index(index: i32) -> Option<PlayerSide> {
+        match index {
+            0 => Some(PlayerSide::Left),
+            1 => Some(PlayerSide::Right),
+            _ => None
+        }
+    }
+
+    fn from_name(name: &str) -> Option<PlayerSide> {
+        match name {
+            "left" => Some(PlayerSide::Left),
+            "right" => Some(PlayerSide::Right),
+            _ => None
+        }
+    }
+}
+
+// the paddles were at 128H and 384H, the same distance either side of the net at 256H
+fn paddle_hclk(side: &PlayerSide) -> i32 {
+    let offset = config().bind().paddle_offset_hclk;
+    match side {
+        PlayerSide::Left => 256 - offset,
+        PlayerSide::Right => 256 + offset,
+    }
+}
+
+#[derive(GodotClass)]
+#[class(base=Area2D)]
+struct Paddle {
+    ypos: f32,
+    side: PlayerSide,
+    polygon: Gd<Polygon2D>,
+    collision_segments: Vec<Gd<CollisionShape2D>>,
+    segment_outlines: Vec<Gd<Line2D>>,
+    // yvel returned by each collision segment, top to bottom, see segment_yvel_table
+    segment_yvels: Vec<i32>,
+    ai: Option<AiPlayer>,
+    touch_target: Option<f32>,
+    // where the paddle spawns, and where a computer paddle drifts back to between returns
+    start_y: f32,
+    // driven by set_target_y instead of input or the computer, for bots and test harnesses
+    commanded: bool,
+    command_target: f32,
+    // up then down, when each key went down and how long it was held for taps that were
+    // released again before the next tick, so they still move the paddle, see buffer_key
+    key_pressed_at: [Option<u64>; 2],
+    key_tapped_usec: [u64; 2],
+    // GameConfig::blanking, read once per tick rather than on every position conversion
+    blanking: (i32, i32),
+    base: Base<Area2D>
+}
+
+#[godot_api]
+impl IArea2D for Paddle {
+    fn init(base: Base<Area2D>) -> Self {
+        let blanking = config().bind().blanking();
+        let init_y = vclk_to_ypos(PADDLE_START_VCLK, blanking.1);
+        Self {
+            ypos: init_y,
+            side: PlayerSide::Left,
+            polygon: Polygon2D::new_alloc(),
+            collision_segments: Vec::new(),
+            segment_outlines: Vec::new(),
+            segment_yvels: Vec::new(),
+            ai: None,
+            touch_target: None,
+            start_y: init_y,
+            commanded: false,
+            command_target: init_y,
+            key_pressed_at: [None, None],
+            key_tapped_usec: [0, 0],
+            blanking,
+            base
+        }
+    }
+
+    fn ready(&mut self) {
+        let polygon = self.polygon.clone();
+        self.base_mut().add_child(polygon.upcast());
+        self.draw();
+        if config().bind().high_contrast {
+            let outline = contrast_outline(hclk_to_px(4), vclk_to_px(16));
+            self.base_mut().add_child(outline.upcast());
+        }
+        self.set_collision_segments();
+        self.base_mut().set_surface_layers();
+        let callable = self.base().callable("on_paddle_area_shape_entered");
+        self.base_mut().connect("area_shape_entered".into(), callable);
+        config().connect("config_changed".into(), self.base().callable("on_config_changed"));
+    }
+
+    fn physics_process(&mut self, delta: f64) {
+        let Some(delta) = physics_delta(delta) else { return };
+        self.blanking = config().bind().blanking();
+        let input = Input::singleton();
+        let xpos = self.paddle_xpos();
+        if self.commanded {
+            let target_y = self.command_target;
+            self.move_towards(target_y, delta);
+        } else if self.ai.is_some() {
+            self.ai_move(delta);
+        } else if let Some(target_y) = self.touch_target {
+            self.move_towards(target_y, delta);
+        } else {
+            let (up_action, dn_action) = self.key_actions();
+            let up_delta = self.take_key_delta(0, input.is_action_pressed(up_action.into()), delta);
+            let dn_delta = self.take_key_delta(1, input.is_action_pressed(dn_action.into()), delta);
+            if up_delta > 0.0 { self.move_up(up_delta) }
+            if dn_delta > 0.0 { self.move_down(dn_delta) }
+        }
+        let pos = Vector2::new(xpos as f32, self.ypos as f32);
+        self.base_mut().set_global_position(pos);
+        let show_collision_segments = config().bind().show_collision_segments;
+        for outline in self.segment_outlines.iter_mut() {
+            outline.set_visible(show_collision_segments);
+        }
+    }
+
+    // input rather than unhandled_input, the pause menu would otherwise eat the arrow keys
+    // a paddle key takes back control from a touch
+    fn input(&mut self, event: Gd<InputEvent>) {
+        let (up_action, dn_action) = self.key_actions();
+        self.buffer_key(0, &event, up_action);
+        self.buffer_key(1, &event, dn_action);
+        if event.is_action_pressed(up_action.into()) || event.is_action_pressed(dn_action.into()) {
+            self.touch_target = None;
+        }
+    }
+
+    // touches on each half of the screen steer that side's paddle until they are lifted
+    // dragging with the mouse does the same, for testing in the editor
+    // positions come in window coordinates, the canvas transform takes them back to the field
+    // through any camera zoom or scaled render resolution
+    fn unhandled_input(&mut self, event: Gd<InputEvent>) {
+        if !config().bind().touch_controls || self.ai.is_some() {
+            return
+        }
+        let to_field = self.base().get_canvas_transform().affine_inverse();
+        let position = if let Ok(touch) = event.clone().try_cast::<InputEventScreenTouch>() {
+            let on_our_half = self.is_on_our_half(to_field * touch.get_position());
+            if !touch.is_pressed() {
+                if on_our_half {
+                    self.touch_target = None;
+                }
+                return
+            }
+            touch.get_position()
+        } else if let Ok(drag) = event.clone().try_cast::<InputEventScreenDrag>() {
+            drag.get_position()
+        } else if let Ok(motion) = event.try_cast::<InputEventMouseMotion>() {
+            if !Input::singleton().is_mouse_button_pressed(MouseButton::LEFT) {
+                return
+            }
+            motion.get_position()
+        } else {
+            return
+        };
+        let position = to_field * position;
+        if self.is_on_our_half(position) {
+            self.touch_target = Some(position.y - vclk_to_px(16) as f32 / 2.0);
+        }
+    }
+}
+
+#[godot_api]
+impl Paddle {
+    fn is_on_our_half(&self, position: Vector2) -> bool {
+        match self.side {
+            PlayerSide::Left => position.x < VIEWPORT_WIDTH as f32 / 2.0,
+            PlayerSide::Right => position.x >= VIEWPORT_WIDTH as f32 / 2.0,
+        }
+    }
+
+    // start_vclk is clamped to the same range the paddle can move in
+    fn from_side(side: PlayerSide, start_vclk: i32) -> Gd<Self> {
+        let blanking = config().bind().blanking();
+        let init_y = vclk_to_ypos(start_vclk, blanking.1).clamp(Paddle::min_ypos(blanking.1), Paddle::max_ypos(Paddle::height_px()));
+        Gd::from_init_fn(|base| {
+            Self {
+                ypos: init_y,
+                side,
+                polygon: Polygon2D::new_alloc(),
+                collision_segments: Vec::new(),
+                segment_outlines: Vec::new(),
+                segment_yvels: Vec::new(),
+                ai: None,
+                touch_target: None,
+                start_y: init_y,
+                commanded: false,
+                command_target: init_y,
+                key_pressed_at: [None, None],
+                key_tapped_usec: [0, 0],
+                blanking,
+                base
+            }
+        })
+    }
+
+    fn enable_ai(&mut self, balls: Vec<Gd<Ball>>, difficulty: Difficulty) {
+        self.ai = Some(AiPlayer {
+            balls,
+            difficulty,
+            aim_offset: 0.0,
+            approaching: false,
+        });
+    }
+
+    // 0 is easy, 1 is medium, 2 is hard, only has an effect on computer controlled paddles
+    #[func]
+    fn set_difficulty(&mut self, level: i32) {
+        if let Some(ai) = self.ai.as_mut() {
+            ai.difficulty = Difficulty::from_level(level);
+        }
+    }
+
+    // heads for where the ball is predicted to cross the paddle, missing by a random
+    // amount that is picked each time the ball starts coming towards this side
+    // when the ball is heading away, drift back to the starting position
+    fn ai_move(&mut self, delta: f64) {
+        let xpos = self.paddle_xpos();
+        let side = self.side.clone();
+        let start_y = self.start_y;
+        let Some(ai) = self.ai.as_mut() else { return };
+        let Some(target) = ai.target_ball(&side, xpos) else { return };
+        let ball = target.bind();
+        let approaching = ball.is_approaching(&side);
+        if approaching && !ai.approaching {
+            ai.aim_offset = randf_range(-1.0, 1.0) as f32 * ai.difficulty.max_aim_error();
+        }
+        ai.approaching = approaching;
+        let target_y = if approaching {
+            ai_target_y(ball.predict_y_at_x(xpos), ball_size_px().1 as f32, ai.aim_offset)
+        } else {
+            start_y
+        };
+        drop(ball);
+        let ai_delta = delta * ai.difficulty.speed_scale();
+        self.move_towards(target_y, ai_delta);
+    }
+
+    // while on, the paddle ignores its keys and the computer player and moves towards set_target_y
+    #[func]
+    fn set_commanded(&mut self, enabled: bool) {
+        self.commanded = enabled;
+    }
+
+    // y of the top of the paddle, moved towards at the normal paddle speed so it can't cheat
+    // setting a target also turns on commanded mode
+    #[func]
+    fn set_target_y(&mut self, y: f32) {
+        self.commanded = true;
+        self.command_target = y.clamp(Paddle::min_ypos(self.blanking.1), Paddle::max_ypos(Paddle::height_px()));
+    }
+
+    // (up, down) actions for this paddle after mirroring and inverting
+    fn key_actions(&self) -> (&'static str, &'static str) {
+        let (up_action, dn_action) = config().bind().paddle_actions(&self.side);
+        let inverted = config().bind().invert_controls[self.side.index()];
+        if inverted { (dn_action, up_action) } else { (up_action, dn_action) }
+    }
+
+    // polling alone misses a tap that goes down and up between two ticks, so presses and
+    // releases are timestamped as they arrive and the time held is kept until the next tick
+    fn buffer_key(&mut self, key: usize, event: &Gd<InputEvent>, action: &str) {
+        let now = Time::singleton().get_ticks_usec();
+        if event.is_action_pressed(action.into()) && self.key_pressed_at[key].is_none() {
+            self.key_pressed_at[key] = Some(now);
+        } else if event.is_action_released(action.into()) {
+            if let Some(pressed_at) = self.key_pressed_at[key].take() {
+                self.key_tapped_usec[key] += now.saturating_sub(pressed_at);
+            }
+        }
+    }
+
+    // how long to move for this tick, the whole tick while the key is down, otherwise
+    // however long it was tapped for since the last tick, but never more than a tick
+    // a held key has been paid for up to now, so its press is restarted from this tick and
+    // releasing it only buffers the part of a tick since then
+    fn take_key_delta(&mut self, key: usize, held: bool, delta: f64) -> f64 {
+        let tapped = std::mem::take(&mut self.key_tapped_usec[key]) as f64 / 1_000_000.0;
+        if held {
+            if let Some(pressed_at) = self.key_pressed_at[key].as_mut() {
+                *pressed_at = Time::singleton().get_ticks_usec();
+            }
+            delta
+        } else {
+            tapped.min(delta)
+        }
+    }
+
+    // the single source of the paddle's horizontal position, for drawing, moving and aiming
+    fn paddle_xpos(&self) -> f32 {
+        hclk_to_xpos(paddle_hclk(&self.side), self.blanking.0)
+    }
+
+    fn min_ypos(vblank: i32) -> f32 {
+        vclk_to_ypos(32, vblank)
+    }
+
+    // i assume the lowest a paddle can reach would also be around 16V from the bottom of the screen
+    // this is where the bottom edge stops, whatever the height of the paddle
+    fn floor_ypos() -> f32 {
+        (VIEWPORT_HEIGHT - vclk_to_px(16)) as f32
+    }
+
+    // the paddle is 16V tall
+    fn height_px() -> f32 {
+        vclk_to_px(16) as f32
+    }
+
+    // top of a paddle of height_px resting on the floor line
+    fn max_ypos(height_px: f32) -> f32 {
+        Paddle::floor_ypos() - height_px
+    }
+
+    // step px further down, a paddle of any height stops with its bottom edge on the floor line
+    fn step_down(ypos: f32, step: f32, height_px: f32) -> f32 {
+        (ypos + step).min(Paddle::max_ypos(height_px))
+    }
+
+    // distance the paddle moves in delta seconds
+    fn paddle_step(delta: f64) -> f32 {
+        let speed_scale = config().bind().game_speed.multiplier();
+        PADDLE_MOVE_BY * VIEWPORT_HEIGHT as f32 * delta as f32 * speed_scale
+    }
+
+    // moves at the normal paddle speed, stopping once within a frame's movement of target_y
+    fn move_towards(&mut self, target_y: f32, delta: f64) {
+        self.ypos = Paddle::step_towards(self.ypos, target_y, Paddle::paddle_step(delta), self.blanking.1);
+    }
+
+    // one tick of move_towards, a step up or down between the top and the floor line
+    fn step_towards(ypos: f32, target_y: f32, step: f32, vblank: i32) -> f32 {
+        if target_y < ypos - step {
+            (ypos - step).max(Paddle::min_ypos(vblank))
+        } else if target_y > ypos + step {
+            Paddle::step_down(ypos, step, Paddle::height_px())
+        } else {
+            ypos
+        }
+    }
+
+    // the paddle was triggered at when the 128H clock signal went high and was 4H wide
+    // it was composed of 15 'segments,' each composed of one HSYNC, or one line
+    // the ball's vertical velocity is determined by which segment it hits
+    // the new vertices are always pushed, this might be slow--consider only updating when y changes
+    fn draw(&mut self) {
+        POLYGON_REBUILDS.fetch_add(1, Ordering::Relaxed);
+        let xpos = self.paddle_xpos();
+        let pos = Vector2::new(xpos as f32, self.ypos as f32);
+        self.base_mut().set_global_position(pos);
+        let bat_height = vclk_to_px(16);
+        let bat_width = hclk_to_px(4);
+        let rect = Rect::new(0, 0, bat_width, bat_height);
+        self.polygon.add_rect(&rect);
+        let color = config().bind().paddle_color(&self.side);
+        self.polygon.set_color(color);
+    }
+
+    fn set_collision_segments(&mut self) {
+        let bat_width = hclk_to_px(4);
+        let accurate = config().bind().accurate;
+        let segment_count = config().bind().paddle_segments;
+        self.segment_yvels = config().bind().segment_yvel_table();
+        for (offset_vclk, height_vclk) in paddle_segment_layout(segment_count, accurate) {
+            let segment_height = height_vclk * PX_UNIT_HEIGHT;
+            let offset = offset_vclk * PX_UNIT_HEIGHT;
+            let mut segment = CollisionShape2D::new_alloc();
+            let mut collision_shape = RectangleShape2D::new_gd();
+            collision_shape.set_size(Vector2::new(bat_width as f32, segment_height));
+            // shapes are centered on their position, line them up with the drawn paddle
+            segment.set_position(Vector2::new(bat_width as f32 / 2.0, offset + segment_height / 2.0));
+            segment.set_shape(collision_shape.upcast());
+            self.base_mut().add_child(segment.clone().upcast());
+            self.collision_segments.push(segment);
+        }
+        self.set_segment_outlines();
+    }
+
+    // debug outlines drawn over each collision segment, one color per segment
+    // the shapes are centered on their position, so the outlines are too
+    fn set_segment_outlines(&mut self) {
+        let segments = self.collision_segments.clone();
+        let segment_count = segments.len();
+        for (i, mut segment) in segments.into_iter().enumerate() {
+            let Some(shape) = segment.get_shape() else { continue };
+            let half_size = shape.cast::<RectangleShape2D>().get_size() / 2.0;
+            let mut outline = Line2D::new_alloc();
+            outline.set_points(PackedVector2Array::from(&[
+                Vector2::new(-half_size.x, -half_size.y),
+                Vector2::new(half_size.x, -half_size.y),
+                Vector2::new(half_size.x, half_size.y),
+                Vector2::new(-half_size.x, half_size.y),
+            ]));
+            outline.set_closed(true);
+            outline.set_width(1.0);
+            outline.set_default_color(Color::from_hsv(i as f64 / segment_count as f64, 1.0, 1.0));
+            outline.set_visible(false);
+            segment.add_child(outline.clone().upcast());
+            self.segment_outlines.push(outline);
+        }
+    }
+
+    // the paddles actually could not move the entire range
+    // based on watching old pong footage, it looks like the maximum range tops
+    // out at the top line of the score counter, or 32V
+    fn move_up(&mut self, delta: f64) {
+        let min_ypos = Paddle::min_ypos(self.blanking.1);
+        let new_ypos = self.ypos - Paddle::paddle_step(delta);
+        if new_ypos >= min_ypos {
+            self.ypos = new_ypos
+        } else {
+            self.ypos = min_ypos
+        }
+    }
+
+    fn move_down(&mut self, delta: f64) {
+        self.ypos = Paddle::step_down(self.ypos, Paddle::paddle_step(delta), Paddle::height_px());
+    }
+
+    // the color and segment table are picked up straight away, the collision shapes
+    // and the paddle's position are only rebuilt with the paddle at the start of the next game
+    #[func]
+    fn on_config_changed(&mut self) {
+        let color = config().bind().paddle_color(&self.side);
+        self.polygon.set_color(color);
+        self.segment_yvels = config().bind().segment_yvel_table();
+    }
+
+    // dims rather than brightens, so it shows up whatever color the paddle is
+    fn hit_flash(&mut self) {
+        if !config().bind().paddle_hit_flash {
+            return
+        }
+        let mut color = config().bind().paddle_color(&self.side);
+        color.a = 0.4;
+        self.polygon.set_color(color);
+        if let Some(mut tree) = self.base().get_tree() {
+            if let Some(mut timer) = tree.create_timer(PADDLE_FLASH_DURATION) {
+                timer.connect("timeout".into(), self.base().callable("end_hit_flash"));
+            }
+        }
+    }
+
+    #[func]
+    fn end_hit_flash(&mut self) {
+        let color = config().bind().paddle_color(&self.side);
+        self.polygon.set_color(color);
+    }
+
+    // the ball is as tall as several segments, so it can enter more than one at once
+    // the segment is picked from where the middle of the ball is rather than whichever shape fired first
+    #[func]
+    fn on_paddle_area_shape_entered(&mut self, _area_rid: Variant, area: Gd<Area2D>, _area_shape_index: i32, _local_shape_index: i32) {
+        if let Ok(mut area) = area.try_cast::<Ball>() {
+            area.bind_mut().paddle_contacts += 1;
+            // a ball already heading away, e.g. clipped again by the paddle moving after it,
+            // would otherwise be turned back into this paddle's own goal
+            if !area.bind().is_approaching(&self.side) {
+                return
+            }
+            if !area.bind().has_collided {
+                area.bind_mut().has_collided = true;
+                area.bind_mut().collided_time = 0.0;
+                let accurate = config().bind().accurate;
+                let segment_count = config().bind().paddle_segments;
+                let ball_center = area.bind().pos.y + ball_size_px().1 as f32 / 2.0;
+                let offset_vclk = (ball_center - self.ypos) / PX_UNIT_HEIGHT;
+                let local_shape_index = paddle_segment_at(offset_vclk, segment_count, accurate);
+                let yvel = self.segment_yvels.get(local_shape_index as usize).copied().unwrap_or(0);
+                area.bind_mut().yvel = yvel;
+                area.bind_mut().xvel *= -1;
+                area.bind_mut().hit_counter += 1;
+                area.bind_mut().turbo = false;
+                let hit_counter = area.bind().hit_counter;
+                area.emit_signal("rally_hit".into(), &[hit_counter.to_variant()]);
+                emit_game_event("paddle_hit", local_shape_index.to_variant());
+                self.hit_flash();
+            }
+        }
+    }
+}
+
+// the steepest the original could return the ball, see yvel_to_height_sec
+const AUTHENTIC_MAX_YVEL: i32 = 3;
+// the simplified paddle, see paddle_segment_layout
+const DEFAULT_PADDLE_SEGMENTS: i32 = 7;
+const MAX_YVEL_LIMIT: i32 = 5;
+
+// one velocity for each of the 15 lines of the original paddle, see Paddle::draw
+// the middle three lines return the ball flat and each angle either side gets a pair of lines,
+// so unlike the simplified 7 segment paddle the flat middle is 3V rather than 4V
+const PADDLE_LINE_YVEL: [i32; 15] = [-3, -3, -2, -2, -1, -1, 0, 0, 0, 1, 1, 2, 2, 3, 3];
+
+// (offset, height) in V of each paddle segment from the top of the paddle
+// accurate mode has one segment per line, see PADDLE_LINE_YVEL, with the last line's
+// segment also covering the 16th line of the drawn paddle so nothing can pass through it
+// the simplified 7 segment paddle is 2V segments with a double height segment in the middle
+// any other count splits the 16V paddle evenly
+fn paddle_segment_layout(count: i32, accurate: bool) -> Vec<(f32, f32)> {
+    if accurate {
+        let last = PADDLE_LINE_YVEL.len() - 1;
+        (0..=last).map(|line| (line as f32, if line == last { 2.0 } else { 1.0 })).collect()
+    } else if count == 7 {
+        vec![(0.0, 2.0), (2.0, 2.0), (4.0, 2.0), (6.0, 4.0), (10.0, 2.0), (12.0, 2.0), (14.0, 2.0)]
+    } else {
+        let height = 16.0 / count as f32;
+        (0..count).map(|i| (i as f32 * height, height)).collect()
+    }
+}
+
+// index of the segment at offset_vclk from the top of the paddle, a ball clipping
+// the top or bottom edge counts as hitting the outermost segment
+fn paddle_segment_at(offset_vclk: f32, count: i32, accurate: bool) -> i32 {
+    let layout = paddle_segment_layout(count, accurate);
+    let index = layout.iter()
+        .position(|(offset, height)| offset_vclk < offset + height)
+        .unwrap_or(layout.len() - 1);
+    index as i32
+}
+
+// the middle segment returns the ball flat and the edge segments return it at +-max_yvel,
+// with the segments in between spread evenly, so the original 7 segments at 3 map one step per segment
+// segments within deadzone of the middle are flattened as well, see GameConfig::flat_deadzone
+fn segment_to_yvel(index: i32, count: i32, accurate: bool, max_yvel: i32, deadzone: i32) -> i32 {
+    if accurate {
+        let yvel = PADDLE_LINE_YVEL.get(index as usize).copied().unwrap_or(0);
+        return scale_yvel(yvel, AUTHENTIC_MAX_YVEL, max_yvel)
+    }
+    if !(0..count).contains(&index) || count / 2 == 0 || (index - count / 2).abs() <= deadzone {
+        return 0
+    }
+    scale_yvel(index - count / 2, count / 2, max_yvel)
+}
+
+fn scale_yvel(yvel: i32, from_max: i32, to_max: i32) -> i32 {
+    let scaled = (yvel * to_max) as f32 / from_max as f32;
+    (scaled.round() as i32).clamp(-to_max, to_max)
+}
+
+// FirstTo is the original rule, the first player to WIN_SCORE wins
+// WinByTwo keeps the game going past WIN_SCORE until one player leads by two
+// SuddenDeath is WinByTwo until both players reach WIN_SCORE, then the next point wins
+#[derive(Clone, Copy, PartialEq)]
+enum ScoringRule {
+    FirstTo,
+    WinByTwo,
+    SuddenDeath,
+}
+
+impl ScoringRule {
+    // under SuddenDeath a tie at WIN_SCORE or above isn't over, the point after it is
+    fn is_game_over(&self, score: [i32; 2]) -> bool {
+        let [left, right] = score;
+        let leading_score = left.max(right);
+        match self {
+            ScoringRule::FirstTo => leading_score >= WIN_SCORE,
+            ScoringRule::WinByTwo => leading_score >= WIN_SCORE && (left - right).abs() >= 2,
+            ScoringRule::SuddenDeath => {
+                let trailing_score = left.min(right);
+                leading_score >= WIN_SCORE && ((left - right).abs() >= 2 || (trailing_score >= WIN_SCORE && left != right))
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl Difficulty {
+    fn from_level(level: i32) -> Difficulty {
+        match level {
+            x if x <= 0 => Difficulty::Easy,
+            1 => Difficulty::Medium,
+            _ => Difficulty::Hard,
+        }
+    }
+
+    // how far from the predicted intercept the paddle can aim, in pixels
+    // the paddle is 16V tall, so anything over half of that can miss outright
+    fn max_aim_error(&self) -> f32 {
+        match self {
+            Difficulty::Easy => vclk_to_px(20) as f32,
+            Difficulty::Medium => vclk_to_px(10) as f32,
+            Difficulty::Hard => vclk_to_px(4) as f32,
+        }
+    }
+
+    // fraction of the player paddle speed
+    fn speed_scale(&self) -> f64 {
+        match self {
+            Difficulty::Easy => 0.4,
+            Difficulty::Medium => 0.7,
+            Difficulty::Hard => 1.0,
+        }
+    }
+}
+
+struct AiPlayer {
+    balls: Vec<Gd<Ball>>,
+    difficulty: Difficulty,
+    aim_offset: f32,
+    approaching: bool,
+}
+
+impl AiPlayer {
+    // the ball heading this way that is closest to the paddle, or any ball if none are
+    fn target_ball(&self, side: &PlayerSide, xpos: f32) -> Option<Gd<Ball>> {
+        let balls: Vec<&Gd<Ball>> = self.balls.iter().filter(|ball| ball.is_instance_valid()).collect();
+        let distance = |ball: &Gd<Ball>| (ball.bind().pos.x - xpos).abs();
+        balls.iter()
+            .filter(|ball| ball.bind().is_approaching(side))
+            .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+            .or(balls.first())
+            .map(|ball| (*ball).clone())
+    }
+}
+
+#[derive(GodotClass)]
+#[class(base=Node2D)]
+struct ScoreDisplay {
+    score: [i32; 2],
+    games_won: [i32; 2],
+    scoring_rule: ScoringRule,
+    polygon: Gd<Polygon2D>,
+    // left then right, only shown with GameConfig::numeral_scores
+    numeral_labels: [Gd<Label>; 2],
+    blink_time: f64,
+    base: Base<Node2D>
+}
+
+#[godot_api]
+impl INode2D for ScoreDisplay {
+    fn init(base: Base<Node2D>) -> Self {
+        Self {
+            score: [0, 0],
+            games_won: [0, 0],
+            scoring_rule: config().bind().scoring_rule,
+            polygon: Polygon2D::new_alloc(),
+            numeral_labels: [Label::new_alloc(), Label::new_alloc()],
+            blink_time: 0.0,
+            base
+        }
+    }
+
+    fn ready(&mut self) {
+        let polygon = self.polygon.clone();
+        self.base_mut().add_child(polygon.upcast());
+        self.polygon.set_color(config().bind().color);
+        // each label covers both digit positions and is right aligned, so a one digit score sits
+        // where the segment display's ones digit would
+        let (hblank, vblank) = config().bind().blanking();
+        for (player, mut label) in self.numeral_labels.clone().into_iter().enumerate() {
+            let ones_hclk = 175 + (player as i32)*192;
+            label.set_position(Vector2::new(hclk_to_xpos(ones_hclk - 32, hblank), vclk_to_ypos(32, vblank)));
+            label.set_size(Vector2::new(hclk_to_px(48) as f32, vclk_to_px(33) as f32));
+            label.set_horizontal_alignment(HorizontalAlignment::RIGHT);
+            label.add_theme_font_size_override("font_size".into(), vclk_to_px(33));
+            label.add_theme_color_override("font_color".into(), config().bind().color);
+            self.base_mut().add_child(label.upcast());
+        }
+        config().connect("config_changed".into(), self.base().callable("on_config_changed"));
+    }
+
+    fn process(&mut self, delta: f64) {
+        self.blink_time += delta;
+        self.draw_seven_segment();
+    }
+}
+
+#[godot_api]
+impl ScoreDisplay {
+    #[signal]
+    fn score_updated();
+
+    #[signal]
+    fn game_over(winner: GString);
+
+    //    _a_
+    // f |_g_| b
+    // e |___| c
+    //     d
+    // converts to an array of values [a, b, c, d, e, f, g]
+    fn n_to_seven_segment(n: i32) -> Option<[i8; 7]> {
+        match n {
+            0 => Some([1, 1, 1, 1, 1, 1, 0]),
+            1 => Some([0, 1, 1, 0, 0, 0, 0]),
+            2 => Some([1, 1, 0, 1, 1, 0, 1]),
+            3 => Some([1, 1, 1, 1, 0, 0, 1]),
+            4 => Some([0, 1, 1, 0, 0, 1, 1]),
+            5 => Some([1, 0, 1, 1, 0, 1, 1]),
+            6 => Some([1, 0, 1, 1, 1, 1, 1]),
+            7 => Some([1, 1, 1, 0, 0, 0, 0]),
+            8 => Some([1, 1, 1, 1, 1, 1, 1]),
+            9 => Some([1, 1, 1, 0, 0, 1, 1]),
+            _ => None
+        }
+    }
+
+    // the score windows were positioned 32V from the top of the screen
+    // for two digit scores, the numbers were 4H apart from each other
+    // the leftmost edge was at 144H, so the next leftmost would be at 160H
+    // for P2 on the right, the leftmost segment was at 336H and the second digit was at 352H
+    // name the horizontal segments 'rows' and the vertical segments 'cols'
+    fn draw_seven_segment(&mut self) {
+        POLYGON_REBUILDS.fetch_add(1, Ordering::Relaxed);
+        let numerals = config().bind().numeral_scores;
+        let mut hidden = [false; 2];
+        for (player, score) in self.score.iter().enumerate() {
+            let blinking_off = self.is_blinking_off(player);
+            let label = &mut self.numeral_labels[player];
+            label.set_visible(numerals);
+            label.set_text(if blinking_off { GString::new() } else { score.to_string().into() });
+            // the pips for games won are still drawn below
+            hidden[player] = blinking_off || numerals;
+        }
+        let digit_style = config().bind().digit_style;
+        let blanking = config().bind().blanking();
+        let mut vertices = PackedVector2Array::new();
+        ScoreDisplay::score_vertices(&mut vertices, self.score, self.games_won, hidden, digit_style, blanking);
+        self.polygon.set_polygon(vertices);
+        polygon_set_indices(&mut self.polygon);
+    }
+
+    // the score's segments and pips, kept apart from the node so the vertices can be built
+    // (and timed, see PerfHud::benchmark_draw_path) without a scene
+    fn score_vertices(vertices: &mut PackedVector2Array, score: [i32; 2], games_won: [i32; 2], hidden: [bool; 2], digit_style: DigitStyle, blanking: (i32, i32)) {
+        let offset_vclk = 32;
+        for (player, score) in score.iter().enumerate() {
+            let ones_digit = score % 10;
+            let tens_digit = score / 10;
+            // trick to calculate offsets using the indices of the scores
+            let ones_hclk = 175 + (player as i32)*192;
+            // make a list of rects, then zip/map with the n_to_seven_segment and draw only if 1
+            if tens_digit != 0 && !hidden[player] {
+                let tens_seg = ScoreDisplay::n_to_seven_segment(tens_digit).unwrap();
+                let tens_hclk = ones_hclk - 32;
+                let tens_seg_rects = digit_style.segment_rects(tens_hclk, offset_vclk, blanking);
+                for (seg_is_on, seg_rect) in iter::zip(tens_seg, tens_seg_rects) {
+                    if seg_is_on == 1 { set_vertices_from_rect(vertices, &seg_rect) }
+                }
+            }
+            let ones_seg = if hidden[player] { [0; 7] } else { ScoreDisplay::n_to_seven_segment(ones_digit).unwrap() };
+            let ones_seg_rects = digit_style.segment_rects(ones_hclk, offset_vclk, blanking);
+            for (seg_is_on, seg_rect) in iter::zip(ones_seg, ones_seg_rects) {
+                if seg_is_on == 1 { set_vertices_from_rect(vertices, &seg_rect) }
+            }
+            // games won in the current match are shown as pips under the score
+            for game in 0..games_won[player] {
+                let pip_hclk = ones_hclk - 32 + game*8;
+                set_vertices_from_rect(vertices, &Rect::<i32>::from_clk(pip_hclk, offset_vclk+40, 4, 4, blanking));
+            }
+        }
+    }
+
+    fn is_game_over(&self) -> bool {
+        self.is_game_over_at(self.score)
+    }
+
+    fn is_game_over_at(&self, score: [i32; 2]) -> bool {
+        self.scoring_rule.is_game_over(score)
+    }
+
+    // game point for the player whose next point would win, the game point under win by two
+    // ends as soon as the other player ties it up, and nothing blinks once the game is over
+    fn is_blinking_off(&self, player: usize) -> bool {
+        if !config().bind().game_point_blink || self.is_game_over() {
+            return false
+        }
+        let mut next_score = self.score;
+        next_score[player] += 1;
+        let off_phase = (self.blink_time / GAME_POINT_BLINK_PERIOD) as i64 % 2 == 1;
+        off_phase && self.is_game_over_at(next_score)
+    }
+
+    // the point after this one decides the game
+    fn is_sudden_death(&self) -> bool {
+        let [left, right] = self.score;
+        self.scoring_rule == ScoringRule::SuddenDeath && left == right && left >= WIN_SCORE
+    }
+
+    // a new scoring rule applies from the next point on
+    #[func]
+    fn on_config_changed(&mut self) {
+        let color = config().bind().color;
+        self.polygon.set_color(color);
+        for label in self.numeral_labels.iter_mut() {
+            label.add_theme_color_override("font_color".into(), color);
+        }
+        self.scoring_rule = config().bind().scoring_rule;
+    }
+
+    // kept for scenes that still report the scorer as "left" or "right"
+    #[func]
+    fn on_score(&mut self, side: GString) {
+        match PlayerSide::from_name(&side.to_string()) {
+            Some(side) => self.add_point(side),
+            None => godot_warn!("scored by unknown side {side}"),
+        }
+    }
+
+    // connected to Wall::scored
+    #[func]
+    fn on_side_scored(&mut self, side: i32) {
+        match PlayerSide::from_index(side) {
+            Some(side) => self.add_point(side),
+            None => godot_warn!("scored by unknown side {side}"),
+        }
+    }
+
+    // a finished game stays finished, any ball still reaching a wall doesn't count
+    fn add_point(&mut self, side: PlayerSide) {
+        if self.is_game_over() {
+            return
+        }
+        let was_sudden_death = self.is_sudden_death();
+        self.score[side.index()] += 1;
+        if self.is_game_over() {
+            self.base_mut().emit_signal("game_over".into(), &[Variant::from(side.as_str())]);
+            emit_game_event("game_over", Variant::from(side.as_str()));
+            return
+        }
+        self.base_mut().emit_signal("score_updated".into(), &[]);
+        if !was_sudden_death && self.is_sudden_death() {
+            emit_game_event("sudden_death", Variant::nil());
+        }
+    }
+
+    // jumps straight to a score, e.g. to practice from 10-10
+    // score_updated isn't emitted since no point was played, so no serve is queued
+    #[func]
+    fn set_score(&mut self, left: i32, right: i32) {
+        let max_score = SCORE_DISPLAY_MAX;
+        if !(0..=max_score).contains(&left) || !(0..=max_score).contains(&right) {
+            godot_warn!("score {left}-{right} can't be shown, must be between 0 and {max_score}");
+            return
+        }
+        self.score = [left, right];
+        self.draw_seven_segment();
+        if self.is_game_over() {
+            let winner = if left > right { PlayerSide::Left } else { PlayerSide::Right };
+            self.base_mut().emit_signal("game_over".into(), &[Variant::from(winner.as_str())]);
+            emit_game_event("game_over", Variant::from(winner.as_str()));
+        }
+    }
+}
+
+// the rects for segments [a, b, c, d, e, f, g] of a digit, as (hclk, vclk, w, h) from its top left
+// Chunky is the original 4 clock thick segments, Thin halves them for a lighter look
+// only the geometry differs, which segments are lit still comes from n_to_seven_segment
+#[derive(Clone, Copy)]
+enum DigitStyle {
+    Chunky,
+    Thin
+}
+
+const CHUNKY_DIGIT_SEGMENTS: [(i32, i32, i32, i32); 7] = [
+    (0, 0, 16, 4), (12, 0, 4, 16), (12, 16, 4, 16), (0, 29, 16, 4), (0, 16, 4, 16), (0, 0, 4, 16), (0, 13, 16, 4),
+];
+const THIN_DIGIT_SEGMENTS: [(i32, i32, i32, i32); 7] = [
+    (0, 0, 16, 2), (14, 0, 2, 16), (14, 16, 2, 16), (0, 31, 16, 2), (0, 16, 2, 16), (0, 0, 2, 16), (0, 15, 16, 2),
+];
+
+impl DigitStyle {
+    fn from_name(name: &str) -> Option<DigitStyle> {
+        match name {
+            "chunky" => Some(DigitStyle::Chunky),
+            "thin" => Some(DigitStyle::Thin),
+            _ => None
+        }
+    }
+
+    fn segment_rects(&self, hclk: i32, vclk: i32, blanking: (i32, i32)) -> [Rect<i32>; 7] {
+        let table = match self {
+            DigitStyle::Chunky => CHUNKY_DIGIT_SEGMENTS,
+            DigitStyle::Thin => THIN_DIGIT_SEGMENTS,
+        };
+        table.map(|(x, y, w, h)| Rect::<i32>::from_clk(hclk + x, vclk + y, w, h, blanking))
+    }
+}
+
+// which way the ball is served after a point
+#[derive(Clone, Copy)]
+enum ServeRule {
+    // the original, the ball carries on towards the player who just missed it
+    ToLoser,
+    ToWinner,
+    AlwaysAlternate,
+}
+
+impl ServeRule {
+    fn from_name(name: &str) -> Option<ServeRule> {
+        match name {
+            "to_loser" => Some(ServeRule::ToLoser),
+            "to_winner" => Some(ServeRule::ToWinner),
+            "alternate" => Some(ServeRule::AlwaysAlternate),
+            _ => None
+        }
+    }
+}
+
+// -1 serves to the left and 1 to the right
+// with no point scored, e.g. the opening serve, the ball keeps the direction it already had
+fn serve_direction(rule: ServeRule, scorer: Option<PlayerSide>, last_direction: i32, xvel: i32) -> i32 {
+    let towards = |side: PlayerSide| if side == PlayerSide::Left { -1 } else { 1 };
+    match (rule, scorer) {
+        (_, None) => if xvel > 0 { 1 } else { -1 },
+        (ServeRule::ToLoser, Some(scorer)) => towards(scorer.opponent()),
+        (ServeRule::ToWinner, Some(scorer)) => towards(scorer),
+        (ServeRule::AlwaysAlternate, Some(_)) => -last_direction,
+    }
+}
+
+// (width, height) of the ball in pixels
+fn ball_size_px() -> (i32, i32) {
+    let size = config().bind().ball_size;
+    (hclk_to_px(size), vclk_to_px(size))
+}
+
+// how far inside a paddle the ball has to be before it can hit a paddle again
+const PADDLE_CLEAR_HCLK: i32 = 16;
+const COLLISION_COOLDOWN: f64 = 0.25;
+
+// when a ball that just hit a paddle can hit one again
+// CenterRange waits until it's back between the paddles, the original fix for the segment collisions
+// AwayFromPaddle waits until it's no longer touching the paddle, which also works near the net
+// Cooldown waits COLLISION_COOLDOWN seconds
+#[derive(Clone, Copy)]
+enum CollisionReset {
+    CenterRange,
+    AwayFromPaddle,
+    Cooldown
+}
+
+impl CollisionReset {
+    fn from_name(name: &str) -> Option<CollisionReset> {
+        match name {
+            "center" => Some(CollisionReset::CenterRange),
+            "away" => Some(CollisionReset::AwayFromPaddle),
+            "cooldown" => Some(CollisionReset::Cooldown),
+            _ => None
+        }
+    }
+}
+
+// where the ball is served from, kept between the points where Ball::process clears
+// has_collided so a ball served from near a paddle can still hit it
+fn ball_spawn() -> Vector2 {
+    let (hclk, vclk) = config().bind().ball_spawn_clk;
+    let hclk = hclk.clamp(
+        paddle_hclk(&PlayerSide::Left) + PADDLE_CLEAR_HCLK,
+        paddle_hclk(&PlayerSide::Right) - PADDLE_CLEAR_HCLK - 1,
+    );
+    let vclk = vclk.clamp(32, VCLK_TOTAL - 32);
+    let (hblank, vblank) = config().bind().blanking();
+    Vector2::new(hclk_to_xpos(hclk, hblank), vclk_to_ypos(vclk, vblank))
+}
+
+const LOB_DURATION: f32 = 1.0;
+const LOB_SPEED_SCALE: f32 = 0.5;
+// radians per second for each unit of combined velocity
+const BALL_SPIN_RATE: f32 = 2.0;
+
+// velocity discretization in the original was as follows:
+// vertical had possible values ranging from 7 to 13
+// the value 10 corresponded to strictly horizontal movement
+// this is a table of screen heights/second
+// val | ht/s
+// 13  | -0.695
+// 12  | -0.462
+// 11  | -0.226
+// 10  |  0
+//  9  |  0.228
+//  8  |  0.455
+//  7  |  0.680
+//
+// horizontal speed increases corresponding to the number of hits
+// hits | wd/s
+// <4   | 0.26
+// 4-11 | 0.39
+// 12+  | 0.53
+
+fn yvel_to_height_sec(yvel: i32) -> f32 {
+    match yvel {
+        -3 => -0.695,
+        -2 => -0.462,
+        -1 => -0.226,
+        0 => 0.0,
+        1 => 0.228,
+        2 => 0.455,
+        3 => 0.680,
+        // steeper than the original, for GameConfig::max_yvel above 3
+        -4 => -0.927,
+        -5 => -1.159,
+        4 => 0.907,
+        5 => 1.134,
+        _ => yvel as f32 * 0.23,
+    }
+}
+
+fn xvel_to_width_sec(xvel: i32) -> f32 {
+    match xvel {
+        -3 => -0.53,
+        -2 => -0.39,
+        -1 => -0.26,
+        0 => 0.0,
+        1 => 0.26,
+        2 => 0.39,
+        3 => 0.53,
+        _ => 0.0,
+    }
+}
+
+// the top of the computer paddle when its middle is aim_offset off the ball's predicted middle
+fn ai_target_y(predicted_y: f32, ball_height: f32, aim_offset: f32) -> f32 {
+    predicted_y + ball_height / 2.0 - Paddle::height_px() / 2.0 + aim_offset
+}
+
+// how far the ball moves in one tick of delta seconds
+// x_scale slows it across the field during a lob, speed_scale is GameConfig::game_speed
+fn ball_step(xvel: i32, yvel: i32, delta: f32, x_scale: f32, speed_scale: f32) -> Vector2 {
+    let x_px_sec = xvel_to_width_sec(xvel) * x_scale * VIEWPORT_WIDTH as f32 * speed_scale;
+    let y_px_sec = yvel_to_height_sec(yvel) * VIEWPORT_HEIGHT as f32 * speed_scale;
+    Vector2::new(x_px_sec * delta, y_px_sec * delta)
+}
+
+// where the ball will be vertically when it reaches target_x
+// VBounds flips the vertical velocity at the top and bottom of the viewport,
+// so the straight line path is folded back into the field
+// a ball that is stationary or moving away from target_x stays where it is
+fn predict_y_at_x(pos: Vector2, xvel: i32, yvel: i32, target_x: f32) -> f32 {
+    let x_px_sec = xvel_to_width_sec(xvel) * VIEWPORT_WIDTH as f32;
+    let y_px_sec = yvel_to_height_sec(yvel) * VIEWPORT_HEIGHT as f32;
+    let time = (target_x - pos.x) / x_px_sec;
+    if !time.is_finite() || time < 0.0 {
+        return pos.y
+    }
+    let field_height = VIEWPORT_HEIGHT as f32;
+    let unfolded_y = (pos.y + y_px_sec * time).rem_euclid(2.0 * field_height);
+    if unfolded_y > field_height {
+        2.0 * field_height - unfolded_y
+    } else {
+        unfolded_y
+    }
+}
+
+#[derive(GodotClass)]
+#[class(base=Area2D)]
+struct Ball {
+    pos: Vector2,
+    xvel: i32,
+    yvel: i32,
+    spawn: Vector2,
+    polygon: Gd<Polygon2D>,
+    collision: Gd<CollisionShape2D>,
+    has_collided: bool,
+    hit_counter: i32,
+    lob_time_left: f32,
+    serve_pending: bool,
+    // when set, each serve waits at spawn for the serving player instead of launching right away
+    serve_lock: bool,
+    parked: bool,
+    // collision diagnostics for the current rally, see the getters below
+    paddle_contacts: i32,
+    missed_overlap_frames: i32,
+    // holds the ball where it went out until the next serve, see GameConfig::freeze_on_score
+    frozen: bool,
+    // cleared when the ball goes past a wall, set again on the next serve
+    in_play: bool,
+    // who scored with this ball since it was last served, decides the next serve's direction
+    last_scorer: Option<PlayerSide>,
+    serve_direction: i32,
+    // seconds since has_collided was set, for CollisionReset::Cooldown
+    collided_time: f64,
+    // a turbo serve travels one speed tier faster until the first return, see GameConfig::turbo_serve
+    turbo: bool,
+    base: Base<Area2D>
+}
+
+#[godot_api]
+impl IArea2D for Ball {
+    fn init(base: Base<Area2D>) -> Self {
+        let spawn = ball_spawn();
+        Self {
+            pos: spawn,
+            xvel: 0,
+            yvel: 0,
+            spawn,
+            polygon: Polygon2D::new_alloc(),
+            collision: CollisionShape2D::new_alloc(),
+            has_collided: false,
+            hit_counter: 0,
+            lob_time_left: 0.0,
+            serve_pending: false,
+            serve_lock: config().bind().serve_lock,
+            parked: false,
+            paddle_contacts: 0,
+            missed_overlap_frames: 0,
+            frozen: false,
+            in_play: true,
+            last_scorer: None,
+            serve_direction: -1,
+            collided_time: 0.0,
+            turbo: false,
+            base
+        }
+    }
+
+    // a new ball is created for every game, so this is the opening serve
+    fn ready(&mut self) {
+        let polygon = self.polygon.clone();
+        let collision = self.collision.clone();
+        self.base_mut().add_child(polygon.upcast());
+        self.base_mut().add_child(collision.upcast());
+        self.base_mut().set_ball_layers();
+        let ball_collisions = config().bind().ball_collisions && config().bind().ball_count > 1;
+        if ball_collisions {
+            let mask = self.base().get_collision_mask();
+            self.base_mut().set_collision_mask(mask | BALL_LAYER);
+            let callable = self.base().callable("on_ball_area_entered");
+            self.base_mut().connect("area_entered".into(), callable);
+        }
+        self.draw();
+        config().connect("config_changed".into(), self.base().callable("on_config_changed"));
+        let texture = config().bind().ball_texture.clone();
+        if let Some(texture) = texture {
+            self.set_texture(texture);
+        } else if config().bind().high_contrast {
+            self.add_contrast_hole();
+        }
+        self.serve_with_lob(true);
+    }
+
+    fn physics_process(&mut self, delta: f64) {
+        let Some(delta) = physics_delta(delta) else { return };
+        if self.frozen {
+            return
+        }
+        if self.parked {
+            if !self.serve_requested() {
+                return
+            }
+            self.parked = false;
+            if let Some(yvel) = self.serve_aim_yvel() {
+                self.yvel = yvel;
+            }
+            self.turbo = self.serve_turbo();
+            emit_game_event("serve", Variant::nil());
+        }
+        let xvel_positive = if self.xvel > 0 { true } else { false };
+        let tier_xvel = {
+            let config = config();
+            let config = config.bind();
+            let tier = config.speed_tier(self.hit_counter);
+            let tier = if self.turbo { (tier + 1).min(config.speed_tier_xvels.len() - 1) } else { tier };
+            config.speed_tier_xvels[tier]
+        };
+        self.xvel = if xvel_positive { tier_xvel } else { -tier_xvel };
+        let mut x_scale = 1.0;
+        if self.lob_time_left > 0.0 {
+            self.lob_time_left -= delta as f32;
+            x_scale = LOB_SPEED_SCALE;
+        }
+        if self.has_collided {
+            self.collided_time += delta;
+            if self.can_collide_again() {
+                self.has_collided = false;
+            }
+        }
+        if !self.has_collided && self.is_overlapping_paddle() {
+            self.missed_overlap_frames += 1;
+        }
+        let speed_scale = config().bind().game_speed.multiplier();
+        self.pos += ball_step(self.xvel, self.yvel, delta as f32, x_scale, speed_scale);
+        // after a point the ball keeps going until it is served again, however long that takes,
+        // so only a ball still in play can have escaped, i.e. it got past a wall without scoring
+        // a ball out in multiball also waits here for the others, see Main::on_ball_out
+        let bounds = Rect::new(
+            -VIEWPORT_WIDTH as f32, -VIEWPORT_HEIGHT as f32,
+            3.0 * VIEWPORT_WIDTH as f32, 3.0 * VIEWPORT_HEIGHT as f32,
+        );
+        let awaiting_serve = !self.in_play || self.serve_pending;
+        if !awaiting_serve && !bounds.contains_point(self.pos) {
+            godot_warn!("ball escaped the field at {}, serving again", self.pos);
+            self.serve();
+            return
+        }
+        let pos = self.pos;
+        self.base_mut().set_global_position(pos);
+        self.spin(delta);
+        let color = self.tier_color();
+        self.polygon.set_color(color);
+    }
+}
+
+#[godot_api]
+impl Ball {
+    // fired on every paddle return with the new length of the rally
+    #[signal]
+    fn rally_hit(count: i32);
 
-// the original circuitry resulted in the net being shifted to the left instead
-// we can add HSHIFT to center everything, or we can turn it off for 'accuracy'
-fn hclk_to_xpos(hclk: i32) -> f32 {
-    let hclk_since_hblank = hclk - HBLANK + HSHIFT;
-    hclk_since_hblank as f32 * PX_UNIT_WIDTH
+    // the original ball was 4H x 4V, see GameConfig::ball_size
+    // the collision shape covers the whole ball, shapes are centered on their position so it
+    // is moved to the middle of the drawn rect
+    fn draw(&mut self) {
+        let spawn = self.spawn;
+        self.base_mut().set_global_position(spawn);
+        let (ball_width, ball_height) = ball_size_px();
+        let rect = Rect::new(0, 0, ball_width, ball_height);
+        self.polygon.add_rect(&rect);
+        self.polygon.set_color(config().bind().color);
+        // pivot around the center of the ball so spinning doesn't shift it
+        let center = Vector2::new(ball_width as f32, ball_height as f32) / 2.0;
+        self.polygon.set_offset(-center);
+        self.polygon.set_position(center);
+        let mut collision_shape = RectangleShape2D::new_gd();
+        collision_shape.set_size(Vector2::new(ball_width as f32, ball_height as f32));
+        self.collision.set_shape(collision_shape.upcast());
+        self.collision.set_position(center);
+    }
+
+    // ball 0 is the normal ball, any extra multiball balls are spread out above and below it
+    // heading alternate ways at different angles
+    fn from_index(index: i32) -> Gd<Self> {
+        let mut ball = Ball::new_alloc();
+        if index > 0 {
+            let mut ball = ball.bind_mut();
+            let direction = if index % 2 == 1 { 1 } else { -1 };
+            ball.spawn.y += ((index + 1) / 2 * direction * vclk_to_px(24)) as f32;
+            ball.pos = ball.spawn;
+            ball.xvel = direction;
+            ball.yvel = index % 3 - 1;
+        }
+        ball
+    }
+
+    #[func]
+    fn serve(&mut self) {
+        self.serve_with_lob(false);
+    }
+
+    // punches a dark square out of the middle so the ball reads as a ring next to the solid paddles
+    // it is a child of the polygon so it spins along with it
+    fn add_contrast_hole(&mut self) {
+        let (ball_width, ball_height) = ball_size_px();
+        let mut hole = Polygon2D::new_alloc();
+        hole.add_rect(&Rect::new(-ball_width / 4, -ball_height / 4, ball_width / 2, ball_height / 2));
+        hole.set_color(Color::BLACK);
+        self.polygon.add_child(hole.upcast());
+    }
+
+    // the color is looked up every frame already, see tier_color
+    #[func]
+    fn on_config_changed(&mut self) {
+        self.serve_lock = config().bind().serve_lock;
+    }
+
+    // a hit sets has_collided so the other segments the ball is touching don't count as hits too,
+    // this decides when the ball can hit a paddle again, see CollisionReset
+    fn can_collide_again(&self) -> bool {
+        let collision_reset = config().bind().collision_reset;
+        match collision_reset {
+            // renable collision when ball is clear of the net (to fix issues with segment collision)
+            // clear means 16H inside either paddle, wherever the paddles have been moved to
+            CollisionReset::CenterRange => {
+                let hblank = config().bind().hblank;
+                let area_clear_range = hclk_to_xpos(paddle_hclk(&PlayerSide::Left) + PADDLE_CLEAR_HCLK, hblank)
+                    ..hclk_to_xpos(paddle_hclk(&PlayerSide::Right) - PADDLE_CLEAR_HCLK, hblank);
+                area_clear_range.contains(&self.pos.x)
+            }
+            // the hit already sent the ball back the other way, so once it's off the paddle it's moving away
+            CollisionReset::AwayFromPaddle => !self.is_overlapping_paddle(),
+            CollisionReset::Cooldown => self.collided_time >= COLLISION_COOLDOWN,
+        }
+    }
+
+    // pos, xvel, yvel, hit_counter and has_collided, enough to replay this ball's path exactly
+    #[func]
+    fn get_state(&self) -> Dictionary {
+        let mut state = Dictionary::new();
+        state.set(GString::from("pos"), self.pos);
+        state.set(GString::from("xvel"), self.xvel);
+        state.set(GString::from("yvel"), self.yvel);
+        state.set(GString::from("hit_counter"), self.hit_counter);
+        state.set(GString::from("has_collided"), self.has_collided);
+        state
+    }
+
+    // missing keys are left as they are, the node is moved straight away rather than next tick
+    #[func]
+    fn set_state(&mut self, state: Dictionary) {
+        self.pos = state_value(&state, "pos", self.pos);
+        self.xvel = state_int(&state, "xvel", self.xvel);
+        self.yvel = state_int(&state, "yvel", self.yvel);
+        self.hit_counter = state_int(&state, "hit_counter", self.hit_counter);
+        self.has_collided = state_value(&state, "has_collided", self.has_collided);
+        let pos = self.pos;
+        self.base_mut().set_global_position(pos);
+    }
+
+    // stretches the texture over the ball in place of the square, the collision is left alone
+    // the sprite is a child of the polygon so it still spins, the polygon just stops drawing itself
+    fn set_texture(&mut self, texture: Gd<Texture2D>) {
+        let (ball_width, ball_height) = ball_size_px();
+        let texture_size = texture.get_size();
+        let mut sprite = Sprite2D::new_alloc();
+        sprite.set_texture(texture);
+        sprite.set_scale(Vector2::new(ball_width as f32 / texture_size.x, ball_height as f32 / texture_size.y));
+        self.polygon.set_self_modulate(Color::from_rgba(1.0, 1.0, 1.0, 0.0));
+        self.polygon.add_child(sprite.upcast());
+    }
+
+    fn is_approaching(&self, side: &PlayerSide) -> bool {
+        match side {
+            PlayerSide::Left => self.xvel < 0,
+            PlayerSide::Right => self.xvel > 0,
+        }
+    }
+
+    // follows the same <4, 4-11 and 12+ hit buckets used for the horizontal speed
+    fn tier_color(&self) -> Color {
+        let config = config();
+        let config = config.bind();
+        if !config.speed_tier_colors {
+            return config.color
+        }
+        match config.speed_tier(self.hit_counter) {
+            0 => config.color,
+            1 => Color::from_rgb(1.0, 1.0, 0.0),
+            _ => Color::from_rgb(1.0, 0.2, 0.2),
+        }
+    }
+
+    // only the polygon is rotated, the collision shape stays axis-aligned
+    fn spin(&mut self, delta: f64) {
+        if !config().bind().ball_spin {
+            self.polygon.set_rotation(0.0);
+            return
+        }
+        let speed = (self.xvel.abs() + self.yvel.abs()) as f32;
+        let direction = self.xvel.signum() as f32;
+        let rotation = self.polygon.get_rotation() + direction * speed * BALL_SPIN_RATE * delta as f32;
+        self.polygon.set_rotation(rotation % std::f32::consts::TAU);
+    }
+
+    // a lob serve starts out below the slowest speed in the table to telegraph the start of a game
+    fn serve_with_lob(&mut self, lob: bool) {
+        self.frozen = false;
+        self.parked = self.serve_lock;
+        if !self.parked {
+            emit_game_event("serve", Variant::nil());
+        }
+        self.serve_pending = false;
+        self.in_play = true;
+        let rule = config().bind().serve_rule;
+        self.serve_direction = serve_direction(rule, self.last_scorer.take(), self.serve_direction, self.xvel);
+        self.xvel = self.serve_direction;
+        self.hit_counter = 0;
+        self.turbo = !self.parked && self.serve_turbo();
+        self.paddle_contacts = 0;
+        self.missed_overlap_frames = 0;
+        self.lob_time_left = if lob { LOB_DURATION } else { 0.0 };
+        let spawn = self.spawn;
+        self.pos = spawn;
+        self.base_mut().set_global_position(spawn);
+    }
+
+    // equal masses, so an elastic collision just swaps the velocities, speed tier included
+    // both balls get the signal, only the one with the lower instance id acts on it
+    #[func]
+    fn on_ball_area_entered(&mut self, area: Gd<Area2D>) {
+        let Ok(mut other) = area.try_cast::<Ball>() else { return };
+        if self.base().instance_id().to_i64() > other.instance_id().to_i64() {
+            return
+        }
+        let mut other = other.bind_mut();
+        let live = |ball: &Ball| ball.in_play && !ball.parked && !ball.frozen;
+        if !live(self) || !live(&other) {
+            return
+        }
+        std::mem::swap(&mut self.xvel, &mut other.xvel);
+        std::mem::swap(&mut self.yvel, &mut other.yvel);
+        std::mem::swap(&mut self.hit_counter, &mut other.hit_counter);
+    }
+
+    // a paddle hit sets has_collided as soon as the shapes touch, so overlapping
+    // a paddle without it means the hit was never registered
+    fn is_overlapping_paddle(&self) -> bool {
+        self.base()
+            .get_overlapping_areas()
+            .iter_shared()
+            .any(|area| area.try_cast::<Paddle>().is_ok())
+    }
+
+    // times a paddle's area_shape_entered fired for this ball during the rally
+    #[func]
+    fn get_paddle_contacts(&self) -> i32 {
+        self.paddle_contacts
+    }
+
+    // frames this rally the ball spent inside a paddle without the hit registering
+    #[func]
+    fn get_missed_overlap_frames(&self) -> i32 {
+        self.missed_overlap_frames
+    }
+
+    // the ball keeps heading the same way after a point, so the server is on the side it moves away from
+    // a computer controlled server launches straight away
+    fn serve_requested(&self) -> bool {
+        let input = Input::singleton();
+        if input.is_action_pressed("serve".into()) {
+            return true
+        }
+        let config = config();
+        let config = config.bind();
+        if self.xvel > 0 {
+            input.is_action_pressed(config.paddle_actions(&PlayerSide::Left).0.into())
+        } else {
+            config.ai_difficulty.is_some() || input.is_action_pressed(config.paddle_actions(&PlayerSide::Right).0.into())
+        }
+    }
+
+    // a locked serve goes off at an angle if the server is holding up or down as it launches,
+    // at most 2 so an aimed serve is still returnable, None leaves the yvel as it was
+    // the computer doesn't aim its serves
+    fn serve_aim_yvel(&self) -> Option<i32> {
+        let server = if self.xvel > 0 { PlayerSide::Left } else { PlayerSide::Right };
+        let config = config();
+        let config = config.bind();
+        if server == PlayerSide::Right && config.ai_difficulty.is_some() {
+            return None
+        }
+        let (up_action, dn_action) = config.paddle_actions(&server);
+        let input = Input::singleton();
+        let aim = 2.min(config.max_yvel);
+        match (input.is_action_pressed(up_action.into()), input.is_action_pressed(dn_action.into())) {
+            (true, false) => Some(-aim),
+            (false, true) => Some(aim),
+            _ => None,
+        }
+    }
+
+    // like aiming, only a human server can turbo and only while holding the key as the serve launches
+    fn serve_turbo(&self) -> bool {
+        let server = if self.xvel > 0 { PlayerSide::Left } else { PlayerSide::Right };
+        let config = config();
+        let config = config.bind();
+        if !config.turbo_serve || (server == PlayerSide::Right && config.ai_difficulty.is_some()) {
+            return false
+        }
+        Input::singleton().is_action_pressed(config.turbo_action(&server).into())
+    }
+
+    #[func]
+    fn predict_y_at_x(&self, target_x: f32) -> f32 {
+        predict_y_at_x(self.pos, self.xvel, self.yvel, target_x)
+    }
+
+    // only one serve is ever queued, even if points are scored in quick succession
+    // a ball that's already out of the tree, e.g. freed by a restart, has nothing to serve into
+    #[func]
+    fn on_score_updated(&mut self) {
+        if self.serve_pending {
+            return
+        }
+        let Some(mut tree) = self.base().get_tree() else { return };
+        let Some(mut timer) = tree.create_timer(config().bind().serve_delay) else { return };
+        timer.connect("timeout".into(), self.base().callable("serve"));
+        self.serve_pending = true;
+        self.frozen = config().bind().freeze_on_score;
+    }
 }
 
-fn hclk_to_px(hclk: i32) -> i32 {
-    (hclk as f32 * PX_UNIT_WIDTH) as i32
+#[derive(GodotClass)]
+#[class(base=Area2D)]
+struct Wall {
+    collision: Gd<CollisionPolygon2D>,
+    side: PlayerSide,
+    attract_mode: bool,
+    base: Base<Area2D>
 }
 
-fn vclk_to_ypos(vclk: i32) -> f32 {
-    let vclk_since_vblank = vclk - VBLANK;
-    vclk_since_vblank as f32 * PX_UNIT_HEIGHT
+#[godot_api]
+impl IArea2D for Wall {
+    fn init(base: Base<Area2D>) -> Self {
+        Self {
+            collision: CollisionPolygon2D::new_alloc(),
+            side: PlayerSide::Left,
+            attract_mode: false,
+            base
+        }
+    }
+
+    fn ready(&mut self) {
+        let collision = self.collision.clone();
+        ready_wall(&mut self.base_mut(), collision);
+        self.draw();
+    }
 }
 
-fn vclk_to_px(vclk: i32) -> i32 {
-    (vclk as f32 * PX_UNIT_HEIGHT) as i32
+#[godot_api]
+impl Wall {
+    #[signal]
+    // side is PlayerSide::index of the scorer, 0 for left and 1 for right
+    fn scored(side: i32);
+
+    // the collider is built once the wall is in the tree
+    fn set_side(&mut self, side: PlayerSide) {
+        self.side = side;
+    }
+
+    fn draw(&mut self) {
+        let wall = self.base().clone();
+        draw_wall(&wall, &mut self.collision, &self.side);
+    }
+
+    // just outside the field on the given side, by default a 10px wall 1px past the edge
+    fn rect(side: &PlayerSide, field: Vector2, thickness: i32, offset: i32) -> Rect<i32> {
+        let (field_width, field_height) = (field.x as i32, field.y as i32);
+        match side {
+            PlayerSide::Left => Rect::new(-offset - thickness, 0, thickness, field_height),
+            PlayerSide::Right => Rect::new(field_width + offset, 0, thickness, field_height),
+        }
+    }
+
+    #[func]
+    fn on_viewport_size_changed(&mut self) {
+        self.draw();
+    }
+
+    // wall_thickness or wall_offset may have changed
+    #[func]
+    fn on_config_changed(&mut self) {
+        self.draw();
+    }
+
+    #[func]
+    fn on_wall_area_entered(&mut self, area: Gd<Area2D>) {
+        if let Ok(mut area) = area.try_cast::<Ball>() {
+            // a frozen ball is being held or moved by a replay, it isn't really going out
+            if area.bind().frozen {
+                return
+            }
+            if config().bind().endless_rally && !self.attract_mode {
+                area.bind_mut().on_score_updated();
+            } else if !self.attract_mode {
+                area.bind_mut().in_play = false;
+                let scorer = self.side.opponent();
+                area.bind_mut().last_scorer = Some(scorer.clone());
+                self.base_mut().emit_signal("scored".into(), &[(scorer.index() as i32).to_variant()]);
+                emit_game_event("scored", Variant::from(scorer.as_str()));
+            } else {
+                let mut ball = area.bind_mut();
+                ball.xvel *= -1;
+                if config().bind().attract_english {
+                    let max_yvel = config().bind().max_yvel;
+                    let nudge = randi_range(-1, 1) as i32;
+                    ball.yvel = (ball.yvel + nudge).clamp(-max_yvel, max_yvel);
+                }
+            }
+        }
+    }
+}
+
+// the setup shared by Wall and TrainingWall, which both handle on_wall_area_entered,
+// on_viewport_size_changed and on_config_changed
+fn ready_wall(wall: &mut Gd<Area2D>, collision: Gd<CollisionPolygon2D>) {
+    wall.add_child(collision.upcast());
+    wall.set_surface_layers();
+    let callable = wall.callable("on_wall_area_entered");
+    wall.connect("area_entered".into(), callable);
+    let callable = wall.callable("on_viewport_size_changed");
+    if let Some(mut viewport) = wall.get_viewport() {
+        viewport.connect("size_changed".into(), callable);
+    }
+    config().connect("config_changed".into(), wall.callable("on_config_changed"));
+}
+
+// the field is the viewport the wall is in, the same as VBounds, so the scoring planes
+// follow its edges at any size, including a scaled render resolution
+fn draw_wall(wall: &Gd<Area2D>, collision: &mut Gd<CollisionPolygon2D>, side: &PlayerSide) {
+    let field = wall.get_viewport_rect().size;
+    let (thickness, offset) = (config().bind().wall_thickness, config().bind().wall_offset);
+    collision.set_polygon(PackedVector2Array::new());
+    collision.add_rect(&Wall::rect(side, field, thickness, offset));
+}
+
+// stands in for a Wall during solo practice, sending the ball back like a backboard instead of scoring
+#[derive(GodotClass)]
+#[class(base=Area2D)]
+struct TrainingWall {
+    collision: Gd<CollisionPolygon2D>,
+    side: PlayerSide,
+    base: Base<Area2D>
+}
+
+#[godot_api]
+impl IArea2D for TrainingWall {
+    fn init(base: Base<Area2D>) -> Self {
+        Self {
+            collision: CollisionPolygon2D::new_alloc(),
+            side: PlayerSide::Left,
+            base
+        }
+    }
+
+    fn ready(&mut self) {
+        let collision = self.collision.clone();
+        ready_wall(&mut self.base_mut(), collision);
+        self.draw();
+    }
+}
+
+#[godot_api]
+impl TrainingWall {
+    fn from_side(side: PlayerSide) -> Gd<Self> {
+        Gd::from_init_fn(|base| {
+            Self {
+                collision: CollisionPolygon2D::new_alloc(),
+                side,
+                base
+            }
+        })
+    }
+
+    // in the same place as the Wall it replaces
+    fn draw(&mut self) {
+        let wall = self.base().clone();
+        draw_wall(&wall, &mut self.collision, &self.side);
+    }
+
+    #[func]
+    fn on_viewport_size_changed(&mut self) {
+        self.draw();
+    }
+
+    #[func]
+    fn on_config_changed(&mut self) {
+        self.draw();
+    }
+
+    #[func]
+    fn on_wall_area_entered(&mut self, area: Gd<Area2D>) {
+        if let Ok(mut area) = area.try_cast::<Ball>() {
+            if area.bind().frozen {
+                return
+            }
+            area.bind_mut().xvel *= -1;
+            area.bind_mut().turbo = false;
+            emit_game_event("bounce", Variant::from("training_wall"));
+        }
+    }
+}
+
+#[derive(GodotClass)]
+#[class(base=Area2D)]
+struct VBounds {
+    ceiling: Gd<CollisionPolygon2D>,
+    floor: Gd<CollisionPolygon2D>,
+    base: Base<Area2D>,
+}
+
+#[godot_api]
+impl IArea2D for VBounds {
+    fn init(base: Base<Area2D>) -> Self {
+        Self {
+            ceiling: CollisionPolygon2D::new_alloc(),
+            floor: CollisionPolygon2D::new_alloc(),
+            base
+        }
+    }
+
+    fn ready(&mut self) {
+        self.draw();
+        let ceiling = self.ceiling.clone();
+        let floor = self.floor.clone();
+        let mut base_ref = self.base_mut().clone();
+        let callable = self.base().callable("on_vbounds_area_shape_entered");
+        base_ref.add_child(ceiling.upcast());
+        base_ref.add_child(floor.upcast());
+        base_ref.set_surface_layers();
+        base_ref.connect("area_shape_entered".into(), callable);
+        let callable = self.base().callable("on_viewport_size_changed");
+        if let Some(mut viewport) = self.base().get_viewport() {
+            viewport.connect("size_changed".into(), callable);
+        }
+        config().connect("config_changed".into(), self.base().callable("on_config_changed"));
+    }
+}
+
+#[godot_api]
+impl VBounds {
+    // the ceiling sits directly above the top of the field and the floor directly below the bottom
+    // both span the whole field width, so they are rebuilt whenever the viewport is resized
+    fn draw(&mut self) {
+        let field = self.base().get_viewport_rect().size;
+        let (field_width, field_height) = (field.x as i32, field.y as i32);
+        let thickness = config().bind().bounds_thickness;
+        let ceiling_rect = Rect::new(0, -thickness, field_width, thickness);
+        let floor_rect = Rect::new(0, field_height, field_width, thickness);
+        self.ceiling.set_polygon(PackedVector2Array::new());
+        self.floor.set_polygon(PackedVector2Array::new());
+        self.ceiling.add_rect(&ceiling_rect);
+        self.floor.add_rect(&floor_rect);
+    }
+
+    #[func]
+    fn on_viewport_size_changed(&mut self) {
+        self.draw();
+    }
+
+    // bounds_thickness may have changed
+    #[func]
+    fn on_config_changed(&mut self) {
+        self.draw();
+    }
+
+    #[func]
+    fn on_vbounds_area_shape_entered(_area_rid: Variant, area: Gd<Area2D>, _area_shape_index: i32, local_shape_index: i32) {
+        if let Ok(mut area) = area.try_cast::<Ball>() {
+            let yvel = area.bind().yvel;
+            // 0 index is ceiling
+            // this approach should guard against clipping
+            if local_shape_index == 0 && yvel < 0 {
+                area.bind_mut().yvel *= -1;
+                emit_game_event("bounce", Variant::from("ceiling"));
+            } else if local_shape_index == 1 && yvel > 0 {
+                area.bind_mut().yvel *= -1;
+                emit_game_event("bounce", Variant::from("floor"));
+            }
+        }
+    }
 }
 
+// debug overlay showing the ball's discrete velocities and what they map to in the velocity tables
 #[derive(GodotClass)]
-#[class(base=Node)]
-struct Main {
-    paddle_l: Gd<Paddle>,
-    paddle_r: Gd<Paddle>,
-    ball: Gd<Ball>,
-    wall_l: Gd<Wall>,
-    wall_r: Gd<Wall>,
-    attract_mode: bool,
-    base: Base<Node>
+#[class(base=Label)]
+struct SpeedHud {
+    ball: Option<Gd<Ball>>,
+    base: Base<Label>
 }
 
 #[godot_api]
-impl INode for Main {
-    fn init(base: Base<Node>) -> Self {
+impl ILabel for SpeedHud {
+    fn init(base: Base<Label>) -> Self {
         Self {
-            paddle_l: Paddle::from_side(PlayerSide::Left),
-            paddle_r: Paddle::from_side(PlayerSide::Right),
-            ball: Ball::new_alloc(),
-            wall_l: Wall::new_alloc(),
-            wall_r: Wall::new_alloc(),
-            attract_mode: false,
+            ball: None,
             base
-        } 
+        }
+    }
+
+    fn ready(&mut self) {
+        let pos = Vector2::new(hclk_to_px(4) as f32, vclk_to_px(4) as f32);
+        self.base_mut().set_position(pos);
     }
 
     fn process(&mut self, _delta: f64) {
-        let input = Input::singleton();
-        if input.is_action_pressed("enter".into()) {
-            if self.attract_mode {
-                self.attract_mode = false;
-                self.new_game();
-            }
+        let Some(ball) = self.ball.clone() else { return };
+        if !ball.is_instance_valid() || !self.base().is_visible() {
+            return
         }
+        let ball = ball.bind();
+        let text = format!(
+            "xvel {} ({:.2} wd/s)\nyvel {} ({:.3} ht/s)\nhits {}\ncontacts {} missed frames {}",
+            ball.xvel, xvel_to_width_sec(ball.xvel),
+            ball.yvel, yvel_to_height_sec(ball.yvel),
+            ball.hit_counter,
+            ball.paddle_contacts, ball.missed_overlap_frames,
+        );
+        self.base_mut().set_text(text.into());
     }
+}
 
-    fn ready(&mut self) {
-        self.new_game();
+impl SpeedHud {
+    fn from_ball(ball: Gd<Ball>) -> Gd<Self> {
+        Gd::from_init_fn(|base| {
+            Self {
+                ball: Some(ball),
+                base
+            }
+        })
     }
 }
 
+const SERVE_PROMPT_BLINK_PERIOD: f64 = 0.5;
+
+// blinks above a ball that is parked waiting for its serve, see GameConfig::serve_lock
+// the arrow points at the player who has to serve, it stays hidden while the ball is in play
+#[derive(GodotClass)]
+#[class(base=Label)]
+struct ServePrompt {
+    ball: Option<Gd<Ball>>,
+    blink_time: f64,
+    base: Base<Label>
+}
+
 #[godot_api]
-impl Main {
-    fn clear_children(&mut self) {
-        for mut child in self.base_mut().get_children().iter_shared().skip(1) {
-            child.queue_free();
+impl ILabel for ServePrompt {
+    fn init(base: Base<Label>) -> Self {
+        Self {
+            ball: None,
+            blink_time: 0.0,
+            base
         }
     }
 
-    fn new_game(&mut self) {
-        self.clear_children();
-        self.paddle_l = Paddle::from_side(PlayerSide::Left);
-        self.paddle_r = Paddle::from_side(PlayerSide::Right);
-        self.ball = Ball::new_alloc();
-        self.wall_l = Wall::new_alloc();
-        self.wall_r = Wall::new_alloc();
-        self.base_mut().add_child(Net::new_alloc().upcast());
-        let paddle_l = self.paddle_l.clone();
-        let paddle_r = self.paddle_r.clone();
-        self.base_mut().add_child(paddle_l.upcast());
-        self.base_mut().add_child(paddle_r.upcast());
-        let ball = self.ball.clone();
-        let ball_callable = self.ball.callable("on_score_updated");
-        self.base_mut().add_child(ball.upcast());
-        let wall_l = self.wall_l.clone();
-        self.wall_l.bind_mut().set_side(PlayerSide::Left);
-        let wall_r = self.wall_r.clone();
-        self.wall_r.bind_mut().set_side(PlayerSide::Right);
-        self.base_mut().add_child(wall_l.upcast());
-        self.base_mut().add_child(wall_r.upcast());
-        self.base_mut().add_child(VBounds::new_alloc().upcast());
-        let mut display = ScoreDisplay::new_alloc();
-        let display_callable = display.callable("on_score");
-        self.base_mut().add_child(display.clone().upcast());
+    fn ready(&mut self) {
+        let color = config().bind().color;
+        self.base_mut().add_theme_color_override("font_color".into(), color);
+    }
 
-        self.wall_l.connect("scored".into(), display_callable.clone());
-        self.wall_r.connect("scored".into(), display_callable.clone());
-        display.connect("score_updated".into(), ball_callable.clone());
-        display.connect("game_over".into(), self.base().callable("attract_mode"));
+    fn process(&mut self, delta: f64) {
+        let Some(ball) = self.ball.clone() else { return };
+        if !ball.is_instance_valid() || !ball.bind().parked {
+            self.blink_time = 0.0;
+            self.base_mut().set_visible(false);
+            return
+        }
+        self.blink_time += delta;
+        let ball = ball.bind();
+        let text = if ball.xvel > 0 { "< SERVE" } else { "SERVE >" };
+        let pos = ball.pos - Vector2::new(hclk_to_px(12) as f32, vclk_to_px(16) as f32);
+        drop(ball);
+        let on = (self.blink_time / SERVE_PROMPT_BLINK_PERIOD) as i64 % 2 == 0;
+        self.base_mut().set_text(text.into());
+        self.base_mut().set_global_position(pos);
+        self.base_mut().set_visible(on);
     }
+}
 
-    #[func]
-    fn attract_mode(&mut self) {
-        self.attract_mode = true;
-        self.paddle_l.queue_free();
-        self.paddle_r.queue_free();
-        self.wall_l.bind_mut().attract_mode = true;
-        self.wall_r.bind_mut().attract_mode = true;
-        self.ball.bind_mut().serve();
+impl ServePrompt {
+    fn from_ball(ball: Gd<Ball>) -> Gd<Self> {
+        Gd::from_init_fn(|base| {
+            Self {
+                ball: Some(ball),
+                blink_time: 0.0,
+                base
+            }
+        })
     }
 }
 
-#[derive(Clone)]
-struct Rect<T> {
-    x: T,
-    y: T,
-    w: T,
-    h: T,
+// a faint marker on the paddle line the ball is heading for, at the height it will cross it
+// only reads the ball, so it has no effect on the game itself
+#[derive(GodotClass)]
+#[class(base=Polygon2D)]
+struct PracticeAim {
+    ball: Option<Gd<Ball>>,
+    base: Base<Polygon2D>
 }
 
-impl Into<Rect<f32>> for Rect<i32> {
-    fn into(self) -> Rect<f32> {
-        Rect {
-            x: self.x as f32,
-            y: self.y as f32,
-            w: self.w as f32,
-            h: self.h as f32,
+#[godot_api]
+impl IPolygon2D for PracticeAim {
+    fn init(base: Base<Polygon2D>) -> Self {
+        Self {
+            ball: None,
+            base
         }
     }
-}
 
-impl<T> Rect<T> {
-    fn new(x: T, y: T, w: T, h: T) -> Self {
-        Self { x, y, w, h }
+    fn ready(&mut self) {
+        let rect = Rect::new(0, 0, hclk_to_px(4), vclk_to_px(1));
+        self.base_mut().add_rect(&rect);
+        let mut color = config().bind().color;
+        color.a = 0.4;
+        self.base_mut().set_color(color);
     }
 
-    fn from_clk(hclk: i32, vclk: i32, w: i32, h: i32) -> Rect::<i32> {
-        Rect::<i32> {
-            x: hclk_to_xpos(hclk) as i32,
-            y: vclk_to_ypos(vclk) as i32,
-            w: hclk_to_px(w),
-            h: vclk_to_px(h),
+    fn process(&mut self, _delta: f64) {
+        let Some(ball) = self.ball.clone() else { return };
+        if !ball.is_instance_valid() {
+            return
         }
+        let ball = ball.bind();
+        if ball.xvel == 0 {
+            self.base_mut().set_visible(false);
+            return
+        }
+        let side = if ball.xvel < 0 { PlayerSide::Left } else { PlayerSide::Right };
+        let paddle_x = hclk_to_xpos(paddle_hclk(&side), config().bind().hblank);
+        let ball_center = ball.predict_y_at_x(paddle_x) + ball_size_px().1 as f32 / 2.0;
+        drop(ball);
+        self.base_mut().set_global_position(Vector2::new(paddle_x, ball_center));
+        self.base_mut().set_visible(true);
     }
 }
 
-fn set_vertices_from_rect(vertices: &mut PackedVector2Array, rect: &Rect<i32>) {
-    let rect_f: Rect<f32> = rect.clone().into();
-    vertices.push(Vector2::new(rect_f.x, rect_f.y));
-    vertices.push(Vector2::new(rect_f.x+rect_f.w, rect_f.y));
-    vertices.push(Vector2::new(rect_f.x+rect_f.w, rect_f.y+rect_f.h));
-    vertices.push(Vector2::new(rect_f.x, rect_f.y+rect_f.h));
+impl PracticeAim {
+    fn from_ball(ball: Gd<Ball>) -> Gd<Self> {
+        Gd::from_init_fn(|base| {
+            Self {
+                ball: Some(ball),
+                base
+            }
+        })
+    }
 }
 
-trait AddRect {
-    fn add_rect(&mut self, rect: &Rect<i32>);
+// times the paddle and score polygons have been rebuilt since the PerfHud last checked
+static POLYGON_REBUILDS: AtomicU32 = AtomicU32::new(0);
+
+// debug overlay for checking what the per-frame redraws cost
+#[derive(GodotClass)]
+#[class(base=Label)]
+struct PerfHud {
+    elapsed: f64,
+    rebuilds_per_sec: u32,
+    rebuild_usec: f64,
+    base: Base<Label>
 }
 
-impl AddRect for Polygon2D {
-    fn add_rect(&mut self, rect: &Rect<i32>) {
-        let mut vertices = self.get_polygon();
-        set_vertices_from_rect(&mut vertices, rect);
-        self.set_polygon(vertices);
+#[godot_api]
+impl ILabel for PerfHud {
+    fn init(base: Base<Label>) -> Self {
+        Self {
+            elapsed: 0.0,
+            rebuilds_per_sec: 0,
+            rebuild_usec: 0.0,
+            base
+        }
+    }
+
+    fn ready(&mut self) {
+        let pos = Vector2::new(hclk_to_px(4) as f32, vclk_to_px(200) as f32);
+        self.base_mut().set_position(pos);
+        // a few ms once per game, so the HUD can show what each rebuild costs
+        self.rebuild_usec = PerfHud::time_rebuild(1000);
+    }
+
+    // the rebuild count is sampled once a second, the fps is already averaged by the engine
+    fn process(&mut self, delta: f64) {
+        self.elapsed += delta;
+        if self.elapsed >= 1.0 {
+            let rebuilds = POLYGON_REBUILDS.swap(0, Ordering::Relaxed);
+            self.rebuilds_per_sec = (rebuilds as f64 / self.elapsed) as u32;
+            self.elapsed = 0.0;
+        }
+        if !self.base().is_visible() {
+            return
+        }
+        let fps = Engine::singleton().get_frames_per_second();
+        let text = format!(
+            "fps {:.0}\npolygon rebuilds/s {}\nscore+net rebuild {:.2}us",
+            fps, self.rebuilds_per_sec, self.rebuild_usec,
+        );
+        self.base_mut().set_text(text.into());
     }
 }
 
-impl AddRect for CollisionPolygon2D {
-    fn add_rect(&mut self, rect: &Rect<i32>) {
-        let mut vertices = self.get_polygon();
-        set_vertices_from_rect(&mut vertices, rect);
-        self.set_polygon(vertices);
+#[godot_api]
+impl PerfHud {
+    // times a full score and net rebuild, averaged over the iterations and reported in usec
+    // only the vertices and indices are built, so this runs without a live scene
+    #[func]
+    fn benchmark_draw_path(&self, iterations: i32) -> Dictionary {
+        let iterations = iterations.max(1);
+        let mut result = Dictionary::new();
+        result.set(GString::from("iterations"), iterations);
+        result.set(GString::from("rebuild_usec"), PerfHud::time_rebuild(iterations));
+        result
     }
 }
 
-fn polygon_set_indices(polygon: &mut Gd<Polygon2D>) {
-    let mut polygon_indices = Array::<Variant>::new();
-    let num_vertices = polygon.get_polygon().len();
-    for i in 0..num_vertices/4 {
-        let base = 4*i as i32;
-        polygon_indices.push(PackedInt32Array::from(&[base, base+1, base+2, base+3]).to_variant());
+impl PerfHud {
+    fn time_rebuild(iterations: i32) -> f64 {
+        let field_height = VIEWPORT_HEIGHT;
+        let blanking = config().bind().blanking();
+        let net_left_edge = hclk_to_xpos(256, blanking.0) as i32;
+        let digit_style = config().bind().digit_style;
+
+        let start = Instant::now();
+        for i in 0..iterations {
+            let score = [10, i % 10];
+            let mut vertices = PackedVector2Array::new();
+            ScoreDisplay::score_vertices(&mut vertices, score, [1, 0], [false, false], digit_style, blanking);
+            black_box(quad_indices(vertices.len()));
+            let mut vertices = PackedVector2Array::new();
+            net_vertices(&mut vertices, net_left_edge, field_height);
+            black_box(quad_indices(vertices.len()));
+        }
+        start.elapsed().as_secs_f64() * 1e6 / iterations as f64
     }
-    polygon.set_polygons(polygon_indices);
 }
 
+// shown during attract mode, the key is looked up every frame so it follows any rebinding
 #[derive(GodotClass)]
-#[class(init, base=Polygon2D)]
-struct Net {
-    base: Base<Polygon2D>
+#[class(init, base=Label)]
+struct StartPrompt {
+    base: Base<Label>
 }
 
 #[godot_api]
-impl IPolygon2D for Net {
+impl ILabel for StartPrompt {
     fn ready(&mut self) {
-        self.draw();
+        let (hblank, vblank) = config().bind().blanking();
+        let pos = Vector2::new(hclk_to_xpos(200, hblank), vclk_to_ypos(200, vblank));
+        self.base_mut().set_position(pos);
+        let color = config().bind().color;
+        self.base_mut().add_theme_color_override("font_color".into(), color);
     }
-}
-
-impl Net {
-    // the net is triggered at 256H from the HRST signal
-    // the net is dependent on a 4V signal for the segments, and is only one pulse wide
-    // this means the net should be drawn with roughly 2x8 segments 8px apart
-    fn draw(&mut self) {
-        let net_left_edge = hclk_to_xpos(256) as i32;
-        let net_segment_spacing: usize = vclk_to_px(8).try_into().unwrap();
 
-        let net_width = hclk_to_px(1);
-        let net_height = vclk_to_px(4);
-        for i in (0..VIEWPORT_HEIGHT).step_by(net_segment_spacing) {
-            let i_int = i as i32;
-            let rect = Rect::new(net_left_edge, i_int, net_width, net_height);
-            self.base_mut().add_rect(&rect);
-        }
-        polygon_set_indices(&mut self.base_mut());
+    fn process(&mut self, _delta: f64) {
+        let text = format!("PRESS {} TO START", action_key_name(START_ACTION));
+        self.base_mut().set_text(text.into());
     }
 }
 
-#[derive(Clone)]
-enum PlayerSide {
-    Left,
-    Right
+// whichever key an action is currently bound to, for the on screen prompts
+fn action_key_name(action: &str) -> String {
+    let events = InputMap::singleton().action_get_events(action.into());
+    match events.iter_shared().next() {
+        Some(event) => event.as_text().to_string().to_uppercase(),
+        None => action.to_uppercase(),
+    }
 }
 
+const CONTROLS_HINT_FADE: f64 = 1.0;
+
+// which keys move which paddle, shown until someone presses one of them and then faded out
+// the keys are looked up every frame like StartPrompt, so mirroring and rebinding show up
 #[derive(GodotClass)]
-#[class(base=Area2D)]
-struct Paddle {
-    ypos: f32,
-    side: PlayerSide,
-    polygon: Gd<Polygon2D>,
-    collision_segments: [Gd<CollisionShape2D>; 7],
-    base: Base<Area2D>
+#[class(init, base=Label)]
+struct ControlsHint {
+    fade_left: Option<f64>,
+    base: Base<Label>
 }
 
 #[godot_api]
-impl IArea2D for Paddle {
-    fn init(base: Base<Area2D>) -> Self {
-        let init_y = vclk_to_ypos(120);
-        let segments: [Gd<CollisionShape2D>; 7] = std::array::from_fn(|_| CollisionShape2D::new_alloc());
-        Self {
-            ypos: init_y,
-            side: PlayerSide::Left,
-            polygon: Polygon2D::new_alloc(),
-            collision_segments: segments,
-            base
-        }
-    }
-
+impl ILabel for ControlsHint {
     fn ready(&mut self) {
-        let polygon = self.polygon.clone();
-        self.base_mut().add_child(polygon.upcast());
-        let collision_segments = self.collision_segments.clone();
-        for collision in collision_segments {
-            self.base_mut().add_child(collision.clone().upcast());
-        }
-        self.draw();
-        self.set_collision_segments();
-        let callable = self.base().callable("on_paddle_area_shape_entered");
-        self.base_mut().connect("area_shape_entered".into(), callable);
+        let (hblank, vblank) = config().bind().blanking();
+        let pos = Vector2::new(hclk_to_xpos(176, hblank), vclk_to_ypos(150, vblank));
+        self.base_mut().set_position(pos);
+        let color = config().bind().color;
+        self.base_mut().add_theme_color_override("font_color".into(), color);
     }
 
     fn process(&mut self, delta: f64) {
-        let input = Input::singleton();
-        let xpos = match self.side {
-            PlayerSide::Left => hclk_to_xpos(128),
-            PlayerSide::Right => hclk_to_xpos(128+256),
-        };
-        match self.side {
-            PlayerSide::Left => {
-                if input.is_action_pressed("up_l".into()) { self.move_up(delta) }
-                if input.is_action_pressed("dn_l".into()) { self.move_down(delta)}
-            },
-            PlayerSide::Right => {
-                if input.is_action_pressed("up_r".into()) { self.move_up(delta) }
-                if input.is_action_pressed("dn_r".into()) { self.move_down(delta) }
+        let sides = [PlayerSide::Left, PlayerSide::Right];
+        let actions = sides.map(|side| config().bind().paddle_actions(&side));
+        if let Some(fade_left) = self.fade_left {
+            let fade_left = fade_left - delta;
+            self.fade_left = Some(fade_left);
+            let alpha = (fade_left / CONTROLS_HINT_FADE).max(0.0) as f32;
+            self.base_mut().set_modulate(Color::from_rgba(1.0, 1.0, 1.0, alpha));
+            if alpha <= 0.0 {
+                self.base_mut().queue_free();
+            }
+        } else {
+            let input = Input::singleton();
+            let pressed = actions.iter().any(|(up, dn)| input.is_action_pressed((*up).into()) || input.is_action_pressed((*dn).into()));
+            if pressed {
+                self.fade_left = Some(CONTROLS_HINT_FADE);
             }
         }
-        let pos = Vector2::new(xpos as f32, self.ypos as f32);
-        self.base_mut().set_global_position(pos);
+        let [(up_l, dn_l), (up_r, dn_r)] = actions;
+        let text = format!(
+            "LEFT  {} / {}\nRIGHT  {} / {}",
+            action_key_name(up_l), action_key_name(dn_l),
+            action_key_name(up_r), action_key_name(dn_r),
+        );
+        self.base_mut().set_text(text.into());
     }
 }
 
+// shown once a match is won, START within REMATCH_WINDOW seconds plays another match
+// and otherwise it falls through to attract mode, either way it frees itself
+#[derive(GodotClass)]
+#[class(base=Label)]
+struct MatchOverScreen {
+    winner: PlayerSide,
+    time_left: f64,
+    base: Base<Label>
+}
+
 #[godot_api]
-impl Paddle {
-    fn from_side(side: PlayerSide) -> Gd<Self> {
-        let init_y = vclk_to_ypos(120);
-        let collision_segments: [Gd<CollisionShape2D>; 7] = std::array::from_fn(|_| CollisionShape2D::new_alloc());
-        Gd::from_init_fn(|base| {
-            Self {
-                ypos: init_y,
-                side,
-                polygon: Polygon2D::new_alloc(),
-                collision_segments: collision_segments,
-                base
-            }
-        })
+impl ILabel for MatchOverScreen {
+    fn init(base: Base<Label>) -> Self {
+        Self {
+            winner: PlayerSide::Left,
+            time_left: REMATCH_WINDOW,
+            base
+        }
     }
 
-    // the paddle was triggered at when the 128H clock signal went high and was 4H wide
-    // it was composed of 15 'segments,' each composed of one HSYNC, or one line
-    // the ball's vertical velocity is determined by which segment it hits
-    // the new vertices are always pushed, this might be slow--consider only updating when y changes
-    fn draw(&mut self) {
-        let xpos = match self.side {
-            PlayerSide::Left => hclk_to_xpos(128),
-            PlayerSide::Right => hclk_to_xpos(128+256),
-        };
-        let pos = Vector2::new(xpos as f32, self.ypos as f32);
-        self.base_mut().set_global_position(pos);
-        let bat_height = vclk_to_px(16);
-        let bat_width = hclk_to_px(4);
-        let rect = Rect::new(0, 0, bat_width, bat_height);
-        self.polygon.add_rect(&rect);
+    fn ready(&mut self) {
+        let (hblank, vblank) = config().bind().blanking();
+        let pos = Vector2::new(hclk_to_xpos(200, hblank), vclk_to_ypos(180, vblank));
+        self.base_mut().set_position(pos);
+        let color = config().bind().color;
+        self.base_mut().add_theme_color_override("font_color".into(), color);
     }
 
-    fn set_collision_segments(&mut self) {
-        let bat_width = hclk_to_px(4);
-        let collision_offsets_vclk = [0, 2, 4, 6, 10, 12, 14];
-        let segment_heights = [2, 2, 2, 4, 2, 2, 2];
-        for (i, segment) in self.collision_segments.iter_mut().enumerate() {
-            let segment_height = segment_heights[i] as f32 * PX_UNIT_HEIGHT;
-            let offset = collision_offsets_vclk[i] as f32 * PX_UNIT_HEIGHT;
-            let mut collision_shape = RectangleShape2D::new_gd();
-            collision_shape.set_size(Vector2::new(bat_width as f32, segment_height));
-            segment.set_position(Vector2::new(0.0, offset));
-            segment.set_shape(collision_shape.upcast());
+    fn process(&mut self, delta: f64) {
+        if Input::singleton().is_action_just_pressed(START_ACTION.into()) {
+            self.base_mut().emit_signal("rematch".into(), &[]);
+            self.base_mut().queue_free();
+            return
         }
-    }
-
-    // the paddles actually could not move the entire range
-    // based on watching old pong footage, it looks like the maximum range tops
-    // out at the top line of the score counter, or 32V
-    fn move_up(&mut self, delta: f64) {
-        let min_ypos = vclk_to_ypos(32);
-        let new_ypos = self.ypos - PADDLE_MOVE_BY * VIEWPORT_HEIGHT as f32 * delta as f32;
-        if new_ypos >= min_ypos {
-            self.ypos = new_ypos
-        } else {
-            self.ypos = min_ypos
+        self.time_left -= delta;
+        if self.time_left <= 0.0 {
+            self.base_mut().emit_signal("timed_out".into(), &[]);
+            self.base_mut().queue_free();
+            return
         }
+        let winner = self.winner.as_str().to_uppercase();
+        let seconds = self.time_left.ceil() as i32;
+        let text = format!("{winner} PLAYER WINS\nPRESS {} TO REMATCH ({seconds})", action_key_name(START_ACTION));
+        self.base_mut().set_text(text.into());
     }
+}
 
-    // i assume the maximum would also be around 16V from the bottom of the screen
-    fn move_down(&mut self, delta: f64) {
-        let bat_height = vclk_to_px(16);
-        let max_ypos = (VIEWPORT_HEIGHT - vclk_to_px(16) - bat_height) as f32;
-        let new_ypos = self.ypos + PADDLE_MOVE_BY * VIEWPORT_HEIGHT as f32 * delta as f32;
-        if new_ypos <= max_ypos {
-            self.ypos = new_ypos
-        } else {
-            self.ypos = max_ypos
-        }
-    }
+#[godot_api]
+impl MatchOverScreen {
+    #[signal]
+    fn rematch();
 
-    #[func]
-    fn on_paddle_area_shape_entered(_area_rid: Variant, area: Gd<Area2D>, _area_shape_index: i32, local_shape_index: i32) {
-        if let Ok(mut area) = area.try_cast::<Ball>() {
-            if !area.bind().has_collided {
-                area.bind_mut().has_collided = true;
-                let yvel = match local_shape_index {
-                    0 => -3,
-                    1 => -2,
-                    2 => -1,
-                    3 => 0,
-                    4 => 1,
-                    5 => 2,
-                    6 => 3,
-                    _ => 0,
-                };
-                area.bind_mut().yvel = yvel;
-                area.bind_mut().xvel *= -1;
-                area.bind_mut().hit_counter += 1;
+    #[signal]
+    fn timed_out();
+
+    fn from_winner(winner: PlayerSide) -> Gd<Self> {
+        Gd::from_init_fn(|base| {
+            Self {
+                winner,
+                time_left: REMATCH_WINDOW,
+                base
             }
-        }
+        })
     }
 }
 
+// the full scan including blanking, see the pixel conversion notes at the top
+const HCLK_TOTAL: i32 = 455;
+const VCLK_TOTAL: i32 = 262;
+
+// debug outlines of where the blanking intervals and the active video land on screen
+// red is the whole scan, yellow is HBLANK, blue is VBLANK and green is the active video
+// the blanking regions sit to the left of and above the field, so zoom out to see them
 #[derive(GodotClass)]
 #[class(base=Node2D)]
-struct ScoreDisplay {
-    score: [i32; 2],
-    polygon: Gd<Polygon2D>,
+struct BlankingOverlay {
+    outlines: Vec<Gd<Line2D>>,
     base: Base<Node2D>
 }
 
 #[godot_api]
-impl INode2D for ScoreDisplay {
+impl INode2D for BlankingOverlay {
     fn init(base: Base<Node2D>) -> Self {
         Self {
-            score: [0, 0],
-            polygon: Polygon2D::new_alloc(),
+            outlines: Vec::new(),
             base
         }
     }
 
     fn ready(&mut self) {
-        let polygon = self.polygon.clone();
-        self.base_mut().add_child(polygon.upcast());
+        let colors = [Color::RED, Color::YELLOW, Color::BLUE, Color::GREEN];
+        for color in colors {
+            let mut outline = Line2D::new_alloc();
+            outline.set_closed(true);
+            outline.set_width(1.0);
+            outline.set_default_color(color);
+            self.base_mut().add_child(outline.clone().upcast());
+            self.outlines.push(outline);
+        }
     }
 
+    // the blanking lengths can be changed at runtime, so the outlines follow them
     fn process(&mut self, _delta: f64) {
-        self.draw_seven_segment();
+        if !self.base().is_visible() {
+            return
+        }
+        let (hblank, vblank) = config().bind().blanking();
+        let regions = [
+            (0, 0, HCLK_TOTAL, VCLK_TOTAL),
+            (0, 0, hblank, VCLK_TOTAL),
+            (0, 0, HCLK_TOTAL, vblank),
+            (hblank, vblank, HCLK_TOTAL, VCLK_TOTAL),
+        ];
+        for (outline, (left, top, right, bottom)) in iter::zip(self.outlines.iter_mut(), regions) {
+            let (left, right) = (hclk_to_xpos_unshifted(left, hblank), hclk_to_xpos_unshifted(right, hblank));
+            let (top, bottom) = (vclk_to_ypos(top, vblank), vclk_to_ypos(bottom, vblank));
+            outline.set_points(PackedVector2Array::from(&[
+                Vector2::new(left, top),
+                Vector2::new(right, top),
+                Vector2::new(right, bottom),
+                Vector2::new(left, bottom),
+            ]));
+        }
     }
 }
 
+// toggled with the pause action, Main pauses everything else in the tree while it is open
+// it keeps processing while paused so the same action can close it again
+#[derive(GodotClass)]
+#[class(init, base=Control)]
+struct PauseMenu {
+    buttons: Vec<Gd<Button>>,
+    base: Base<Control>
+}
+
 #[godot_api]
-impl ScoreDisplay {
+impl IControl for PauseMenu {
+    fn ready(&mut self) {
+        self.base_mut().set_process_mode(ProcessMode::ALWAYS);
+        self.base_mut().set_visible(false);
+        let (hblank, vblank) = config().bind().blanking();
+        let pos = Vector2::new(hclk_to_xpos(232, hblank), vclk_to_ypos(112, vblank));
+        self.base_mut().set_position(pos);
+        let color = config().bind().color;
+        let mut menu = VBoxContainer::new_alloc();
+        for (text, method) in [("RESUME", "on_resume"), ("RESTART", "on_restart"), ("QUIT", "on_quit")] {
+            let mut button = Button::new_alloc();
+            button.set_text(text.into());
+            button.set_flat(true);
+            button.add_theme_color_override("font_color".into(), color);
+            button.connect("pressed".into(), self.base().callable(method));
+            menu.add_child(button.clone().upcast());
+            self.buttons.push(button);
+        }
+        self.base_mut().add_child(menu.upcast());
+    }
+
+    fn process(&mut self, _delta: f64) {
+        if Input::singleton().is_action_just_pressed("pause".into()) {
+            let open = !self.base().is_visible();
+            self.set_open(open);
+        }
+    }
+}
+
+#[godot_api]
+impl PauseMenu {
+    // connected to Main::set_paused
     #[signal]
-    fn score_updated();
+    fn pause_requested(paused: bool);
 
     #[signal]
-    fn game_over();
+    fn restart_requested();
 
-    //    _a_
-    // f |_g_| b
-    // e |___| c
-    //     d
-    // converts to an array of values [a, b, c, d, e, f, g]
-    fn n_to_seven_segment(n: i32) -> Option<[i8; 7]> {
-        match n {
-            0 => Some([1, 1, 1, 1, 1, 1, 0]),
-            1 => Some([0, 1, 1, 0, 0, 0, 0]),
-            2 => Some([1, 1, 0, 1, 1, 0, 1]),
-            3 => Some([1, 1, 1, 1, 0, 0, 1]),
-            4 => Some([0, 1, 1, 0, 0, 1, 1]),
-            5 => Some([1, 0, 1, 1, 0, 1, 1]),
-            6 => Some([1, 0, 1, 1, 1, 1, 1]),
-            7 => Some([1, 1, 1, 0, 0, 0, 0]),
-            8 => Some([1, 1, 1, 1, 1, 1, 1]),
-            9 => Some([1, 1, 1, 0, 0, 1, 1]),
-            _ => None
+    // the buttons are focused so they can be picked with the keyboard or a gamepad
+    fn set_open(&mut self, open: bool) {
+        self.base_mut().set_visible(open);
+        self.base_mut().emit_signal("pause_requested".into(), &[open.to_variant()]);
+        if let (true, Some(button)) = (open, self.buttons.first_mut()) {
+            button.grab_focus();
         }
     }
 
-    // the score windows were positioned 32V from the top of the screen
-    // for two digit scores, the numbers were 4H apart from each other
-    // the leftmost edge was at 144H, so the next leftmost would be at 160H
-    // for P2 on the right, the leftmost segment was at 336H and the second digit was at 352H
-    // name the horizontal segments 'rows' and the vertical segments 'cols'
-    fn draw_seven_segment(&mut self) {
-        self.polygon.set_polygon(PackedVector2Array::new());
-        let offset_vclk = 32;
-        for (player, score) in self.score.iter().enumerate() {
-            let ones_digit = score % 10;
-            let tens_digit = score / 10;
-            // trick to calculate offsets using the indices of the scores
-            let ones_hclk = 175 + (player as i32)*192;
-            // make a list of rects, then zip/map with the n_to_seven_segment and draw only if 1
-            if tens_digit != 0 {
-                let tens_seg = ScoreDisplay::n_to_seven_segment(tens_digit).unwrap();
-                let tens_hclk = ones_hclk - 32;
-                let tens_seg_rects = [
-                    Rect::<i32>::from_clk(tens_hclk, offset_vclk, 16, 4),
-                    Rect::<i32>::from_clk(tens_hclk+12, offset_vclk, 4, 16),
-                    Rect::<i32>::from_clk(tens_hclk+12, offset_vclk+16, 4, 16),
-                    Rect::<i32>::from_clk(tens_hclk, offset_vclk+29, 16, 4),
-                    Rect::<i32>::from_clk(tens_hclk, offset_vclk+16, 4, 16),
-                    Rect::<i32>::from_clk(tens_hclk, offset_vclk, 4, 16),
-                    Rect::<i32>::from_clk(tens_hclk, offset_vclk+13, 16, 4),
-                ];
-                for (seg_is_on, seg_rect) in iter::zip(tens_seg, tens_seg_rects) {
-                    if seg_is_on == 1 { self.polygon.add_rect(&seg_rect) }
-                }
-            }
-            let ones_seg = ScoreDisplay::n_to_seven_segment(ones_digit).unwrap();
-            let ones_seg_rects = [
-                Rect::<i32>::from_clk(ones_hclk, offset_vclk, 16, 4),
-                Rect::<i32>::from_clk(ones_hclk+12, offset_vclk, 4, 16),
-                Rect::<i32>::from_clk(ones_hclk+12, offset_vclk+16, 4, 16),
-                Rect::<i32>::from_clk(ones_hclk, offset_vclk+29, 16, 4),
-                Rect::<i32>::from_clk(ones_hclk, offset_vclk+16, 4, 16),
-                Rect::<i32>::from_clk(ones_hclk, offset_vclk, 4, 16),
-                Rect::<i32>::from_clk(ones_hclk, offset_vclk+13, 16, 4),
-            ];
-            for (seg_is_on, seg_rect) in iter::zip(ones_seg, ones_seg_rects) {
-                if seg_is_on == 1 { self.polygon.add_rect(&seg_rect) }
-            }
-        }
-        polygon_set_indices(&mut self.polygon);
+    #[func]
+    fn on_resume(&mut self) {
+        self.set_open(false);
     }
 
     #[func]
-    fn on_score(&mut self, side: GString) {
-        let side = side.to_string();
-        if side == "left".to_string() {
-            self.score[0] += 1;
-            if self.score[0] == WIN_SCORE {
-                self.base_mut().emit_signal("game_over".into(), &[]);
-                return
-            }
-            self.base_mut().emit_signal("score_updated".into(), &[]);
-        } else if side == "right".to_string() {
-            self.score[1] += 1;
-            if self.score[1] == WIN_SCORE {
-                self.base_mut().emit_signal("game_over".into(), &[]);
-                return
-            }
-            self.base_mut().emit_signal("score_updated".into(), &[]);
+    fn on_restart(&mut self) {
+        self.set_open(false);
+        self.base_mut().emit_signal("restart_requested".into(), &[]);
+    }
+
+    #[func]
+    fn on_quit(&mut self) {
+        if let Some(mut tree) = self.base().get_tree() {
+            tree.quit();
         }
     }
 }
 
-// velocity discretization in the original was as follows:
-// vertical had possible values ranging from 7 to 13
-// the value 10 corresponded to strictly horizontal movement
-// this is a table of screen heights/second
-// val | ht/s
-// 13  | -0.695
-// 12  | -0.462
-// 11  | -0.226
-// 10  |  0
-//  9  |  0.228
-//  8  |  0.455
-//  7  |  0.680
-//
-// horizontal speed increases corresponding to the number of hits
-// hits | wd/s
-// <4   | 0.26
-// 4-11 | 0.39
-// 12+  | 0.53
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-#[derive(GodotClass)]
-#[class(base=Area2D)]
-struct Ball {
-    pos: Vector2,
-    xvel: i32,
-    yvel: i32,
-    spawn: Vector2,
-    polygon: Gd<Polygon2D>,
-    collision: Gd<CollisionShape2D>,
-    has_collided: bool,
-    hit_counter: i32,
-    base: Base<Area2D>
-}
+    #[test]
+    fn sudden_death_needs_the_point_after_the_tie() {
+        let rule = ScoringRule::SuddenDeath;
+        assert!(!rule.is_game_over([11, 11]));
+        assert!(rule.is_game_over([12, 11]));
+        assert!(rule.is_game_over([11, 9]));
+        assert!(!rule.is_game_over([11, 10]));
+    }
 
-#[godot_api]
-impl IArea2D for Ball {
-    fn init(base: Base<Area2D>) -> Self {
-        let spawn_x = hclk_to_xpos(256);
-        let spawn_y = vclk_to_ypos(128);
-        Self {
-            pos: Vector2::new(spawn_x, spawn_y),
-            xvel: 0,
-            yvel: 10,
-            spawn: Vector2::new(spawn_x, spawn_y),
-            polygon: Polygon2D::new_alloc(),
-            collision: CollisionShape2D::new_alloc(),
-            has_collided: false,
-            hit_counter: 0,
-            base
-        }
+    // the serve after each point in turn, starting from an opening serve to the left
+    fn serves_after(rule: ServeRule, scorers: &[PlayerSide]) -> Vec<i32> {
+        let mut direction = serve_direction(rule, None, -1, -1);
+        scorers.iter()
+            .map(|scorer| {
+                direction = serve_direction(rule, Some(scorer.clone()), direction, direction);
+                direction
+            })
+            .collect()
     }
 
-    fn ready(&mut self) {
-        let polygon = self.polygon.clone();
-        let collision = self.collision.clone();
-        self.base_mut().add_child(polygon.upcast());
-        self.base_mut().add_child(collision.upcast());
-        self.draw();
-        self.serve();
+    const SCORERS: [PlayerSide; 4] = [PlayerSide::Left, PlayerSide::Left, PlayerSide::Right, PlayerSide::Left];
+
+    #[test]
+    fn serve_to_loser() {
+        assert_eq!(serves_after(ServeRule::ToLoser, &SCORERS), vec![1, 1, -1, 1]);
     }
 
-    fn process(&mut self, delta: f64) {
-        let xvel_positive = if self.xvel > 0 { true } else { false };
-        self.xvel = match self.hit_counter {
-            x if x < 4 => if xvel_positive { 1 } else { -1 },
-            x if x < 12 => if xvel_positive { 2 } else { -2 },
-            x if x >= 12 => if xvel_positive { 3 } else { -3 },
-            _ => 0,
-        };
-        let height_sec = match self.yvel {
-            -3 => -0.695,
-            -2 => -0.462,
-            -1 => -0.226,
-            0 => 0.0,
-            1 => 0.228,
-            2 => 0.455,
-            3 => 0.680,
-            _ => 0.0,
-        };
-        let width_sec = match self.xvel {
-            -3 => -0.53,
-            -2 => -0.39,
-            -1 => -0.26,
-            0 => 0.0,
-            1 => 0.26,
-            2 => 0.39,
-            3 => 0.53,
-            _ => 0.0,
-        };
-        // renable collision when ball is clear of the net (to fix issues with segment collision)
-        let area_clear_range = hclk_to_xpos(144)..hclk_to_xpos(368);
-        if self.has_collided == true && area_clear_range.contains(&self.pos.x) {
-            self.has_collided = false;
-        }
-        let y_px_sec = height_sec * VIEWPORT_HEIGHT as f32;
-        let x_px_sec = width_sec * VIEWPORT_WIDTH as f32;
-        let xpos = x_px_sec * delta as f32;
-        let ypos = y_px_sec * delta as f32;
-        self.pos += Vector2::new(xpos, ypos);
-        let pos = self.pos;
-        self.base_mut().set_global_position(pos);
+    #[test]
+    fn serve_to_winner() {
+        assert_eq!(serves_after(ServeRule::ToWinner, &SCORERS), vec![-1, -1, 1, -1]);
     }
-}
 
-#[godot_api]
-impl Ball {
-    fn draw(&mut self) {
-        let spawn = self.spawn;
-        self.base_mut().set_global_position(spawn);
-        let ball_height = vclk_to_px(4);
-        let ball_width = hclk_to_px(4);
-        let rect = Rect::new(0, 0, ball_width, ball_height);
-        self.polygon.add_rect(&rect);
-        let mut collision_shape = RectangleShape2D::new_gd();
-        collision_shape.set_size(Vector2::new(ball_width as f32, 1.0));
-        self.collision.set_shape(collision_shape.upcast());
+    #[test]
+    fn serve_always_alternates() {
+        assert_eq!(serves_after(ServeRule::AlwaysAlternate, &SCORERS), vec![1, -1, 1, -1]);
     }
 
-    #[func]
-    fn serve(&mut self) {
-        self.hit_counter = 0;
-        let spawn = self.spawn;
-        self.pos = spawn;
-        self.base_mut().set_global_position(spawn);
+    #[test]
+    fn opening_serve_keeps_its_direction() {
+        assert_eq!(serve_direction(ServeRule::ToLoser, None, -1, 1), 1);
+        assert_eq!(serve_direction(ServeRule::AlwaysAlternate, None, 1, -1), -1);
     }
 
-    #[func]
-    fn on_score_updated(&mut self) {
-        let mut timer = self.base().get_tree().unwrap().create_timer(1.5).unwrap();
-        timer.connect("timeout".into(), self.base().callable("serve"));
+    #[test]
+    fn rects_intersect_when_they_overlap() {
+        let rect = Rect::new(0.0, 0.0, 10.0, 10.0);
+        assert!(rect.intersects(&Rect::new(5.0, 5.0, 10.0, 10.0)));
+        assert!(rect.intersects(&Rect::new(2.0, 2.0, 2.0, 2.0)));
+        assert!(!rect.intersects(&Rect::new(20.0, 0.0, 5.0, 5.0)));
     }
-}
 
-#[derive(GodotClass)]
-#[class(base=Area2D)]
-struct Wall {
-    collision: Gd<CollisionPolygon2D>,
-    side: PlayerSide,
-    attract_mode: bool,
-    base: Base<Area2D>
-}
+    #[test]
+    fn touching_rects_dont_intersect() {
+        let rect = Rect::new(0.0, 0.0, 10.0, 10.0);
+        assert!(!rect.intersects(&Rect::new(10.0, 0.0, 10.0, 10.0)));
+        assert!(!rect.intersects(&Rect::new(0.0, 10.0, 10.0, 10.0)));
+    }
 
-#[godot_api]
-impl IArea2D for Wall {
-    fn init(base: Base<Area2D>) -> Self {
-        Self {
-            collision: CollisionPolygon2D::new_alloc(),
-            side: PlayerSide::Left,
-            attract_mode: false,
-            base
+    #[test]
+    fn rect_contains_points_on_its_top_left_edges_only() {
+        let rect = Rect::new(0.0, 0.0, 10.0, 10.0);
+        assert!(rect.contains_point(Vector2::new(0.0, 0.0)));
+        assert!(rect.contains_point(Vector2::new(5.0, 9.5)));
+        assert!(!rect.contains_point(Vector2::new(10.0, 5.0)));
+        assert!(!rect.contains_point(Vector2::new(5.0, 10.0)));
+        assert!(!rect.contains_point(Vector2::new(-0.5, 5.0)));
+    }
+
+    #[test]
+    fn prediction_without_a_bounce_follows_the_line() {
+        let pos = Vector2::new(0.0, 240.0);
+        assert_eq!(predict_y_at_x(pos, 3, 0, 600.0), 240.0);
+        // one second of travel at 3 across and 1 down
+        let x_px_sec = xvel_to_width_sec(3) * VIEWPORT_WIDTH as f32;
+        let y_px_sec = yvel_to_height_sec(1) * VIEWPORT_HEIGHT as f32;
+        let predicted = predict_y_at_x(pos, 3, 1, x_px_sec);
+        assert!((predicted - (240.0 + y_px_sec)).abs() < 0.01);
+    }
+
+    #[test]
+    fn prediction_folds_bounces_back_into_the_field() {
+        let pos = Vector2::new(0.0, 400.0);
+        let x_px_sec = xvel_to_width_sec(3) * VIEWPORT_WIDTH as f32;
+        let y_px_sec = yvel_to_height_sec(3) * VIEWPORT_HEIGHT as f32;
+        let field_height = VIEWPORT_HEIGHT as f32;
+        let predicted = predict_y_at_x(pos, 3, 3, x_px_sec);
+        assert!((predicted - (2.0 * field_height - (400.0 + y_px_sec))).abs() < 0.01);
+        for target_x in [100.0, 300.0, 600.0, 2000.0] {
+            let predicted = predict_y_at_x(pos, 1, -3, target_x);
+            assert!((0.0..=field_height).contains(&predicted));
         }
     }
 
-    fn ready(&mut self) {
-        let collision = self.collision.clone();
-        self.base_mut().add_child(collision.upcast());
-        let callable = self.base().callable("on_wall_area_entered");
-        self.base_mut().connect("area_entered".into(), callable);
+    #[test]
+    fn ball_heading_away_or_standing_still_stays_put() {
+        let pos = Vector2::new(320.0, 100.0);
+        assert_eq!(predict_y_at_x(pos, -3, 2, 600.0), 100.0);
+        assert_eq!(predict_y_at_x(pos, 0, 2, 600.0), 100.0);
+        assert_eq!(predict_y_at_x(pos, 0, 2, 320.0), 100.0);
     }
-}
 
-#[godot_api]
-impl Wall {
-    #[signal]
-    fn scored(side: GString);
+    #[test]
+    fn hard_ai_always_aims_at_the_paddle() {
+        // aiming more than half the 16V paddle off the intercept can miss the ball
+        let half_paddle = vclk_to_px(16) as f32 / 2.0;
+        let [easy, medium, hard] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard].map(|d| d.max_aim_error());
+        assert!(hard < half_paddle);
+        assert!(easy > half_paddle);
+        assert!(hard < medium && medium < easy);
+    }
 
-    fn set_side(&mut self, side: PlayerSide) {
-        match side {
-            PlayerSide::Left => {
-                let position = Rect::new(-11, 0, 10, VIEWPORT_HEIGHT);
-                self.collision.add_rect(&position);
+    #[test]
+    fn harder_ai_is_at_least_as_quick() {
+        let [easy, medium, hard] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard].map(|d| d.speed_scale());
+        assert!(easy < medium && medium < hard);
+        // hard moves as fast as a player can, never faster
+        assert_eq!(hard, 1.0);
+        assert!(matches!(Difficulty::from_level(-1), Difficulty::Easy));
+        assert!(matches!(Difficulty::from_level(5), Difficulty::Hard));
+    }
+
+    // the fraction of seeded rallies the computer returns, each served from the left paddle
+    // at a random height, angle and speed tier and bouncing off the top and bottom like VBounds
+    // a ball crossing above or below where the paddle can reach isn't counted, nobody returns those
+    fn ai_return_rate(difficulty: Difficulty, rallies: i32) -> f32 {
+        let settings = Settings::defaults(false);
+        let (hblank, vblank) = (settings.hblank, settings.vblank);
+        let delta = 1.0 / settings.tick_rate as f32;
+        let ball_px = (hclk_to_px(settings.ball_size) as f32, vclk_to_px(settings.ball_size) as f32);
+        let serve_x = hclk_to_xpos(256 - settings.paddle_offset_hclk + 4, hblank);
+        let paddle_x = hclk_to_xpos(256 + settings.paddle_offset_hclk, hblank);
+        let start_y = vclk_to_ypos(settings.paddle_start_vclk, vblank);
+        let step = PADDLE_MOVE_BY * VIEWPORT_HEIGHT as f32 * delta * difficulty.speed_scale() as f32;
+        // xorshift, so every run sees the same rallies
+        let mut seed: u32 = 0x9e37_79b9;
+        let mut roll = move || {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            seed as f32 / u32::MAX as f32
+        };
+        let (mut returns, mut reachable) = (0, 0);
+        for _ in 0..rallies {
+            let mut pos = Vector2::new(serve_x, roll() * (VIEWPORT_HEIGHT as f32 - ball_px.1));
+            let mut yvel = (roll() * 7.0) as i32 - AUTHENTIC_MAX_YVEL;
+            let xvel = settings.speed_tier_xvels[(roll() * 3.0) as usize % 3];
+            let aim_offset = (roll() * 2.0 - 1.0) * difficulty.max_aim_error();
+            let mut ypos = start_y;
+            while pos.x + ball_px.0 < paddle_x {
+                let target_y = ai_target_y(predict_y_at_x(pos, xvel, yvel, paddle_x), ball_px.1, aim_offset);
+                ypos = Paddle::step_towards(ypos, target_y, step, vblank);
+                pos += ball_step(xvel, yvel, delta, 1.0, 1.0);
+                if (pos.y < 0.0 && yvel < 0) || (pos.y > VIEWPORT_HEIGHT as f32 && yvel > 0) {
+                    yvel = -yvel;
+                }
+            }
+            if pos.y + ball_px.1 <= Paddle::min_ypos(vblank) || pos.y >= Paddle::floor_ypos() {
+                continue
             }
-            PlayerSide::Right => {
-                let position = Rect::new(VIEWPORT_WIDTH+1, 0, 10, VIEWPORT_HEIGHT);
-                self.collision.add_rect(&position);
+            reachable += 1;
+            if pos.y < ypos + Paddle::height_px() && ypos < pos.y + ball_px.1 {
+                returns += 1;
             }
         }
-        self.side = side;
+        returns as f32 / reachable as f32
     }
 
-    #[func]
-    fn on_wall_area_entered(&mut self, area: Gd<Area2D>) {
-        if let Ok(mut area) = area.try_cast::<Ball>() {
-            if !self.attract_mode {
-                match self.side {
-                PlayerSide::Left => self.base_mut().emit_signal("scored".into(), &[Variant::from("right")]),
-                PlayerSide::Right => self.base_mut().emit_signal("scored".into(), &[Variant::from("left")]),
-                };
-            } else {
-                area.bind_mut().xvel *= -1;
-            }
-        }
+    #[test]
+    fn harder_ai_returns_more_of_the_same_rallies() {
+        let [easy, medium, hard] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard].map(|d| ai_return_rate(d, 500));
+        assert!(hard >= 0.95);
+        assert!(medium >= 0.8);
+        assert!(easy <= 0.6);
+        assert!(easy < medium && medium <= hard);
     }
-}
 
-#[derive(GodotClass)]
-#[class(base=Area2D)]
-struct VBounds {
-    ceiling: Gd<CollisionPolygon2D>,
-    floor: Gd<CollisionPolygon2D>,
-    base: Base<Area2D>,
-}
+    #[test]
+    fn simplified_paddle_has_a_double_height_middle() {
+        let segment_at = |offset| paddle_segment_at(offset, DEFAULT_PADDLE_SEGMENTS, false);
+        assert_eq!(segment_at(0.0), 0);
+        assert_eq!(segment_at(1.9), 0);
+        assert_eq!(segment_at(2.0), 1);
+        assert_eq!(segment_at(6.0), 3);
+        assert_eq!(segment_at(9.9), 3);
+        assert_eq!(segment_at(10.0), 4);
+        assert_eq!(segment_at(15.9), 6);
+    }
 
-#[godot_api]
-impl IArea2D for VBounds {
-    fn init(base: Base<Area2D>) -> Self {
-        Self {
-            ceiling: CollisionPolygon2D::new_alloc(),
-            floor: CollisionPolygon2D::new_alloc(),
-            base
+    #[test]
+    fn clipping_the_paddle_edge_hits_the_outer_segment() {
+        assert_eq!(paddle_segment_at(-1.0, DEFAULT_PADDLE_SEGMENTS, false), 0);
+        assert_eq!(paddle_segment_at(17.0, DEFAULT_PADDLE_SEGMENTS, false), 6);
+        assert_eq!(paddle_segment_at(-1.0, 0, true), 0);
+        assert_eq!(paddle_segment_at(17.0, 0, true), 14);
+    }
+
+    #[test]
+    fn other_segment_counts_split_the_paddle_evenly() {
+        assert_eq!(paddle_segment_at(3.9, 4, false), 0);
+        assert_eq!(paddle_segment_at(4.0, 4, false), 1);
+        assert_eq!(paddle_segment_at(12.0, 4, false), 3);
+        for line in 0..15 {
+            assert_eq!(paddle_segment_at(line as f32 + 0.5, 0, true), line);
         }
+        // the drawn paddle's 16th line belongs to the 15th
+        assert_eq!(paddle_segment_at(15.5, 0, true), 14);
     }
 
-    fn ready(&mut self) {
-        let ceiling_rect = Rect::new(0, -10, VIEWPORT_WIDTH, 10);
-        let floor_rect = Rect::new(0, VIEWPORT_HEIGHT, VIEWPORT_WIDTH, 10);
-        self.ceiling.add_rect(&ceiling_rect);
-        self.floor.add_rect(&floor_rect);
-        let ceiling = self.ceiling.clone();
-        let floor = self.floor.clone();
-        let mut base_ref = self.base_mut().clone();
-        let callable = self.base().callable("on_vbounds_area_shape_entered");
-        base_ref.add_child(ceiling.upcast());
-        base_ref.add_child(floor.upcast());
-        base_ref.connect("area_shape_entered".into(), callable);
+    #[test]
+    fn simplified_segments_step_one_yvel_each() {
+        let yvels: Vec<i32> = (0..7).map(|i| segment_to_yvel(i, 7, false, AUTHENTIC_MAX_YVEL, 0)).collect();
+        assert_eq!(yvels, [-3, -2, -1, 0, 1, 2, 3]);
+        assert_eq!(segment_to_yvel(7, 7, false, AUTHENTIC_MAX_YVEL, 0), 0);
+        assert_eq!(segment_to_yvel(-1, 7, false, AUTHENTIC_MAX_YVEL, 0), 0);
     }
-}
 
-#[godot_api]
-impl VBounds {
-    #[func]
-    fn on_vbounds_area_shape_entered(_area_rid: Variant, area: Gd<Area2D>, _area_shape_index: i32, local_shape_index: i32) {
-        if let Ok(mut area) = area.try_cast::<Ball>() {
-            let yvel = area.bind().yvel;
-            // 0 index is ceiling
-            // this approach should guard against clipping
-            if local_shape_index == 0 && yvel < 0 {
-                area.bind_mut().yvel *= -1;
-            } else if local_shape_index == 1 && yvel > 0 {
-                area.bind_mut().yvel *= -1;
+    #[test]
+    fn deadzone_flattens_the_segments_around_the_middle() {
+        let yvels: Vec<i32> = (0..7).map(|i| segment_to_yvel(i, 7, false, AUTHENTIC_MAX_YVEL, 1)).collect();
+        assert_eq!(yvels, [-3, -2, 0, 0, 0, 2, 3]);
+    }
+
+    #[test]
+    fn accurate_lines_scale_to_max_yvel() {
+        let yvels: Vec<i32> = (0..15).map(|i| segment_to_yvel(i, 0, true, AUTHENTIC_MAX_YVEL, 0)).collect();
+        assert_eq!(yvels, PADDLE_LINE_YVEL);
+        assert_eq!(segment_to_yvel(0, 0, true, MAX_YVEL_LIMIT, 0), -MAX_YVEL_LIMIT);
+        assert_eq!(segment_to_yvel(14, 0, true, MAX_YVEL_LIMIT, 0), MAX_YVEL_LIMIT);
+        assert_eq!(yvels.iter().sum::<i32>(), 0);
+        // a flat middle of three lines, where the simplified paddle's is 4V
+        assert_eq!(yvels.iter().filter(|&&yvel| yvel == 0).count(), 3);
+    }
+
+    #[test]
+    fn tall_paddle_moves_down_onto_the_floor_line() {
+        // the floor line is 16V from the bottom of the screen
+        assert_eq!(Paddle::floor_ypos(), (VIEWPORT_HEIGHT - vclk_to_px(16)) as f32);
+        for height_px in [Paddle::height_px(), vclk_to_px(48) as f32] {
+            let mut ypos = 100.0;
+            for _ in 0..1000 {
+                ypos = Paddle::step_down(ypos, 7.5, height_px);
             }
+            assert_eq!(ypos + height_px, Paddle::floor_ypos());
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn defaults_are_the_original_hardware() {
+        let settings = Settings::defaults(false);
+        assert!(settings.color == ColorScheme::White.color());
+        assert!(matches!(settings.scoring_rule, ScoringRule::FirstTo));
+        assert!(settings.scoring_rule.is_game_over([WIN_SCORE, 0]));
+        assert!(!settings.scoring_rule.is_game_over([WIN_SCORE - 1, 0]));
+        assert!(!settings.accurate);
+        assert!(!settings.offset_net);
+        assert!(settings.show_net);
+        assert!(!settings.show_collision_segments);
+        assert!(settings.score_flash);
+        assert!(!settings.paddle_hit_flash);
+        assert!(matches!(settings.digit_style, DigitStyle::Chunky));
+        assert!(!settings.numeral_scores);
+        assert!(!settings.game_point_blink);
+        assert!(settings.paddle_colors.is_none());
+        assert!(settings.ai_difficulty.is_none());
+        assert_eq!(settings.match_length, 1);
+        assert_eq!(settings.serve_delay, 1.5);
+        assert!(!settings.touch_controls);
+        assert!(Settings::defaults(true).touch_controls);
+        assert_eq!(settings.paddle_segments, DEFAULT_PADDLE_SEGMENTS);
+        assert!(!settings.frame_step);
+        assert!(!settings.step_requested);
+        assert_eq!(settings.invert_controls, [false, false]);
+        assert!(matches!(settings.game_speed, GameSpeed::Normal));
+        assert_eq!(settings.game_speed.multiplier(), 1.0);
+        assert!(!settings.ball_spin);
+        assert!(!settings.speed_tier_colors);
+        assert_eq!(settings.speed_tier_hits, [4, 12]);
+        assert_eq!(settings.speed_tier_xvels, [1, 2, 3]);
+        assert!(!settings.practice_mode);
+        assert!(settings.controls_hint);
+        assert!(!settings.endless_rally);
+        assert_eq!(settings.bounds_thickness, 10);
+        assert_eq!((settings.wall_thickness, settings.wall_offset), (10, 1));
+        assert_eq!(settings.paddle_start_vclk, PADDLE_START_VCLK);
+        assert!(!settings.serve_lock);
+        assert!(!settings.turbo_serve);
+        assert!(settings.training_wall.is_none());
+        assert_eq!(settings.max_yvel, AUTHENTIC_MAX_YVEL);
+        assert_eq!(settings.flat_deadzone, 0);
+        assert!(settings.segment_yvels.is_none());
+        assert!(settings.pause_on_focus_loss);
+        assert!(!settings.freeze_on_score);
+        assert_eq!(settings.attract_timeout, Some(20.0));
+        assert!(settings.attract_english);
+        assert!(!settings.mirror_controls);
+        assert_eq!(settings.ball_size, 4);
+        assert_eq!(settings.ball_spawn_clk, (256, 128));
+        assert!(settings.ball_texture.is_none());
+        assert_eq!(settings.ball_count, 1);
+        assert!(!settings.ball_collisions);
+        assert!(!settings.high_contrast);
+        assert_eq!((settings.hblank, settings.vblank), (HBLANK, VBLANK));
+        assert_eq!(settings.paddle_offset_hclk, 128);
+        assert!(matches!(settings.serve_rule, ServeRule::ToLoser));
+        assert!(matches!(settings.collision_reset, CollisionReset::AwayFromPaddle));
+        assert!(settings.render_style.is_none());
+        assert!(settings.render_resolution.is_none());
+        assert_eq!(settings.tick_rate, TICK_RATE);
+        assert!(settings.vsync);
+        assert_eq!(settings.max_fps, 0);
+    }
+
+    #[test]
+    fn ball_past_a_missing_paddle_scores_for_the_opponent() {
+        let settings = Settings::defaults(false);
+        let blanking = (settings.hblank, settings.vblank);
+        let field = Vector2::new(VIEWPORT_WIDTH as f32, VIEWPORT_HEIGHT as f32);
+        let walls = [PlayerSide::Left, PlayerSide::Right].map(|side| {
+            let rect: Rect<f32> = Wall::rect(&side, field, settings.wall_thickness, settings.wall_offset).into();
+            (side, rect)
+        });
+        // the left paddle is up at the top, see paddle_hclk, and the ball is served flat from the middle
+        let paddle: Rect<f32> = Rect::<i32>::from_clk(256 - settings.paddle_offset_hclk, 32, 4, 16, blanking).into();
+        let (hclk, vclk) = settings.ball_spawn_clk;
+        let mut pos = Vector2::new(hclk_to_xpos(hclk, blanking.0), vclk_to_ypos(vclk, blanking.1));
+        let (ball_width, ball_height) = (hclk_to_px(settings.ball_size), vclk_to_px(settings.ball_size));
+        let xvel = -settings.speed_tier_xvels[0];
+        let delta = 1.0 / settings.tick_rate as f32;
+        let mut ticks = 0;
+        let side = loop {
+            let ball = Rect::new(pos.x, pos.y, ball_width as f32, ball_height as f32);
+            assert!(!ball.intersects(&paddle), "the ball hit the paddle at {:?}", pos);
+            if let Some((side, _)) = walls.iter().find(|(_, wall)| ball.intersects(wall)) {
+                break side.clone()
+            }
+            ticks += 1;
+            assert!(ticks < 10 * settings.tick_rate, "the ball never reached a wall");
+            pos += ball_step(xvel, 0, delta, 1.0, settings.game_speed.multiplier());
+        };
+        assert!(side == PlayerSide::Left);
+        assert!(side.opponent() == PlayerSide::Right);
+    }
+}